@@ -1,8 +1,16 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rayon::prelude::*;
 use std::path::PathBuf;
+use wordnet_ls::wordnet::PartOfSpeech;
 use wordnet_ls::wordnet::SemanticRelation;
 use wordnet_ls::wordnet::WordNet;
 
+/// The server, to benchmark rendering as it is done for the editor. Only its rendering is used and
+/// its tests are not run here.
+#[allow(dead_code, unused_imports)]
+#[path = "../src/main.rs"]
+mod server;
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("all_words", |b| {
         b.iter(|| {
@@ -31,6 +39,60 @@ fn criterion_benchmark(c: &mut Criterion) {
             black_box(words)
         })
     });
+    c.bench_function("all_words_cause_ref", |b| {
+        b.iter(|| {
+            let wndir = std::env::var("WNSEARCHDIR").unwrap();
+            let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+            let causes = wn
+                .all_words()
+                .iter()
+                .map(|w| {
+                    PartOfSpeech::iter()
+                        .flat_map(|pos| wn.synset_refs_for(w, pos))
+                        .map(|ss| ss.with_relationship(SemanticRelation::Cause).count())
+                        .sum::<usize>()
+                })
+                .sum::<usize>();
+            black_box(causes)
+        })
+    });
+    c.bench_function("synsets_iter_cause", |b| {
+        let wndir = std::env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        b.iter(|| {
+            let causes = PartOfSpeech::iter()
+                .flat_map(|pos| wn.synsets_iter(pos))
+                .map(|ss| ss.with_relationship(SemanticRelation::Cause).count())
+                .sum::<usize>();
+            black_box(causes)
+        })
+    });
+    c.bench_function("par_synsets_iter_cause", |b| {
+        let wndir = std::env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        b.iter(|| {
+            let causes = PartOfSpeech::iter()
+                .map(|pos| {
+                    wn.par_synsets_iter(pos)
+                        .map(|ss| ss.with_relationship(SemanticRelation::Cause).count())
+                        .sum::<usize>()
+                })
+                .sum::<usize>();
+            black_box(causes)
+        })
+    });
+    c.bench_function("all_info_woman", |b| {
+        let wndir = std::env::var("WNSEARCHDIR").unwrap();
+        let dict = server::Dict::new(&PathBuf::from(wndir)).unwrap();
+        let words = ["woman".to_owned()];
+        b.iter(|| black_box(dict.all_info(&words)))
+    });
+    c.bench_function("all_info_run", |b| {
+        let wndir = std::env::var("WNSEARCHDIR").unwrap();
+        let dict = server::Dict::new(&PathBuf::from(wndir)).unwrap();
+        let words = ["run".to_owned()];
+        b.iter(|| black_box(dict.all_info(&words)))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
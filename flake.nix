@@ -12,7 +12,7 @@
     cargoNix = pkgs.callPackage ./Cargo.nix {};
   in {
     packages.${system} = {
-      wordnet-ls = cargoNix.rootCrate.build;
+      wordnet-ls = cargoNix.rootCrate.build.override {features = ["serde"];};
       default = self.packages.${system}.wordnet-ls;
     };
 
@@ -0,0 +1,120 @@
+// Finding the WordNet dictionary when no location is configured, by looking where the WordNet tools
+// and package managers put it.
+
+use std::path::Path;
+use std::path::PathBuf;
+use wordnet_ls::wordnet::WordNet;
+
+/// The first candidate directory that holds a dictionary.
+pub fn discover() -> Option<PathBuf> {
+    candidates()
+        .into_iter()
+        .find(|dir| WordNet::is_dictionary(dir))
+}
+
+/// Directories to look in, in order of preference.
+fn candidates() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::var_os("WNSEARCHDIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = std::env::var_os("WNHOME") {
+        dirs.push(PathBuf::from(home).join("dict"));
+    }
+    for data_dir in xdg_data_dirs() {
+        dirs.push(data_dir.join("wordnet"));
+        dirs.push(data_dir.join("wordnet").join("dict"));
+    }
+    dirs.push(PathBuf::from("/usr/share/wordnet"));
+
+    // homebrew keeps each version in its own directory, e.g. `Cellar/wordnet/3.1_2/dict`
+    let mut prefixes = Vec::new();
+    if let Some(prefix) = std::env::var_os("HOMEBREW_PREFIX") {
+        prefixes.push(PathBuf::from(prefix));
+    }
+    prefixes
+        .extend(["/opt/homebrew", "/usr/local", "/home/linuxbrew/.linuxbrew"].map(PathBuf::from));
+    for prefix in prefixes {
+        dirs.push(prefix.join("share").join("wordnet"));
+        dirs.extend(subdirs(
+            &prefix.join("Cellar").join("wordnet"),
+            |_| true,
+            "dict",
+        ));
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".nix-profile").join("dict"));
+    }
+    dirs.push(PathBuf::from("/run/current-system/sw/dict"));
+    dirs.extend(subdirs(
+        Path::new("/nix/store"),
+        |name| name.contains("-wordnet-"),
+        "dict",
+    ));
+    dirs
+}
+
+/// `$XDG_DATA_HOME` then `$XDG_DATA_DIRS`, with their defaults.
+fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => dirs.extend(dirs::home_dir().map(|home| home.join(".local").join("share"))),
+    }
+    match std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+    {
+        Some(data_dirs) => dirs.extend(std::env::split_paths(&data_dirs)),
+        None => dirs.extend(["/usr/local/share", "/usr/share"].map(PathBuf::from)),
+    }
+    dirs
+}
+
+/// `child` of each directory in `parent` whose name matches, latest version first.
+fn subdirs(parent: &Path, matches: impl Fn(&str) -> bool, child: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_str().is_some_and(&matches))
+        .map(|entry| entry.path().join(child))
+        .collect::<Vec<_>>();
+    // prefer later versions
+    dirs.sort_unstable();
+    dirs.reverse();
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidate_order() {
+        let dirs = candidates();
+        let position = |dir: &str| dirs.iter().position(|d| d == Path::new(dir));
+        let usr_share = position("/usr/share/wordnet").unwrap();
+        assert!(position("/opt/homebrew/share/wordnet").unwrap() > usr_share);
+        assert!(position("/run/current-system/sw/dict").unwrap() > usr_share);
+        if let Ok(dir) = std::env::var("WNSEARCHDIR") {
+            assert_eq!(dirs[0], PathBuf::from(dir));
+        }
+    }
+
+    #[test]
+    fn versions_newest_first() {
+        let dir = std::env::temp_dir().join(format!("wordnet-ls-cellar-{}", std::process::id()));
+        for version in ["3.0", "3.1_2", "3.1"] {
+            std::fs::create_dir_all(dir.join(version)).unwrap();
+        }
+        let found = subdirs(&dir, |_| true, "dict");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            found,
+            ["3.1_2", "3.1", "3.0"].map(|version| dir.join(version).join("dict"))
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// House glossary of a workspace, read from `GLOSSARY.md` or `.wordnet-ls/glossary.toml` in each
+// workspace folder. Its terms are shown before, and take precedence over, the WordNet meanings.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Glossary files looked for in each workspace folder, relative to the folder.
+pub const GLOSSARY_FILES: &[&str] = &["GLOSSARY.md", ".wordnet-ls/glossary.toml"];
+
+#[derive(Debug, Deserialize)]
+struct GlossaryFile {
+    #[serde(default)]
+    terms: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub term: String,
+    pub definition: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Glossary {
+    /// Terms by their key, see [`key`].
+    terms: BTreeMap<String, Vec<Term>>,
+}
+
+impl Glossary {
+    /// Read the glossaries in the folders, returning messages for any that could not be read.
+    pub fn load(folders: &[PathBuf]) -> (Self, Vec<String>) {
+        let mut glossary = Self::default();
+        let mut errors = Vec::new();
+        for path in folders
+            .iter()
+            .flat_map(|folder| GLOSSARY_FILES.iter().map(|file| folder.join(file)))
+            .filter(|path| path.is_file())
+        {
+            match Self::read(&path) {
+                Ok(terms) => glossary.extend(terms),
+                Err(err) => errors.push(err),
+            }
+        }
+        (glossary, errors)
+    }
+
+    fn read(path: &Path) -> Result<Vec<Term>, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read glossary {path:?}: {e}"))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => {
+                parse_toml(&content).map_err(|e| format!("Invalid glossary {path:?}: {e}"))
+            }
+            _ => Ok(parse_markdown(&content)),
+        }
+    }
+
+    pub fn extend(&mut self, terms: Vec<Term>) {
+        for term in terms {
+            let entries = self.terms.entry(key(&term.term)).or_default();
+            if !entries.contains(&term) {
+                entries.push(term);
+            }
+        }
+    }
+
+    pub fn lookup(&self, word: &str) -> &[Term] {
+        self.terms.get(&key(word)).map_or(&[], |t| t.as_slice())
+    }
+
+    /// Entries for any of the words, in the order of the words.
+    pub fn render(&self, words: &[String]) -> Option<String> {
+        let mut content = String::new();
+        let mut seen = Vec::new();
+        for word in words {
+            for term in self.lookup(word) {
+                if seen.contains(&term) {
+                    continue;
+                }
+                seen.push(term);
+                writeln!(
+                    content,
+                    "**{}** _glossary_\n{}\n",
+                    term.term, term.definition
+                )
+                .unwrap();
+            }
+        }
+        (!content.is_empty()).then(|| content.trim().to_owned())
+    }
+}
+
+/// Terms are matched ignoring case, with spaces as underscores like the words from documents.
+fn key(term: &str) -> String {
+    term.trim().to_lowercase().replace([' ', '\t'], "_")
+}
+
+/// A TOML glossary is a table of terms to their definitions:
+///
+/// ```toml
+/// [terms]
+/// kubelet = "the agent that runs on each node"
+/// ```
+fn parse_toml(content: &str) -> Result<Vec<Term>, String> {
+    let file = toml::from_str::<GlossaryFile>(content).map_err(|e| e.to_string())?;
+    Ok(file
+        .terms
+        .into_iter()
+        .map(|(term, definition)| Term { term, definition })
+        .collect())
+}
+
+/// A markdown glossary can define terms as:
+///
+/// - bold terms, `**term**: definition`, optionally in a list,
+/// - headings, `## term`, followed by a paragraph with the definition,
+/// - definition lists, `term` followed by a line with `: definition`.
+fn parse_markdown(content: &str) -> Vec<Term> {
+    let mut terms = Vec::new();
+    let lines = content.lines().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+        if let Some(heading) = line.strip_prefix("##") {
+            let term = heading.trim_start_matches('#').trim();
+            let paragraph = lines[i..]
+                .iter()
+                .map(|l| l.trim())
+                .skip_while(|l| l.is_empty())
+                .take_while(|l| !l.is_empty() && !l.starts_with('#'))
+                .collect::<Vec<_>>();
+            if !term.is_empty() && !paragraph.is_empty() {
+                terms.push(Term {
+                    term: term.to_owned(),
+                    definition: paragraph.join(" "),
+                });
+            }
+        } else if let Some((term, definition)) = bold_term(line) {
+            terms.push(Term { term, definition });
+        } else if let Some(definition) = lines.get(i).and_then(|l| l.trim().strip_prefix(": ")) {
+            if !line.is_empty() && !line.starts_with('#') {
+                terms.push(Term {
+                    term: line.to_owned(),
+                    definition: definition.trim().to_owned(),
+                });
+                i += 1;
+            }
+        }
+    }
+    terms
+}
+
+/// Split a line like `- **term**: definition` or `**term** - definition`.
+fn bold_term(line: &str) -> Option<(String, String)> {
+    let line = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .unwrap_or(line);
+    let (term, rest) = line.strip_prefix("**")?.split_once("**")?;
+    let term = term.trim().trim_end_matches(':').trim();
+    let definition = rest.trim_start().trim_start_matches([':', '-', '—']).trim();
+    (!term.is_empty() && !definition.is_empty()).then(|| (term.to_owned(), definition.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn markdown() {
+        let terms = parse_markdown(
+            "# Glossary
+
+- **Kubelet**: the agent that runs on each node
+**Pod:** a group of containers
+**Sidecar** - a helper container
+
+## Control plane
+
+The components that manage
+the cluster.
+
+Monorepo
+: a single repository for many projects
+",
+        );
+        let expected = expect![[r#"
+            [
+                Term {
+                    term: "Kubelet",
+                    definition: "the agent that runs on each node",
+                },
+                Term {
+                    term: "Pod",
+                    definition: "a group of containers",
+                },
+                Term {
+                    term: "Sidecar",
+                    definition: "a helper container",
+                },
+                Term {
+                    term: "Control plane",
+                    definition: "The components that manage the cluster.",
+                },
+                Term {
+                    term: "Monorepo",
+                    definition: "a single repository for many projects",
+                },
+            ]
+        "#]];
+        expected.assert_debug_eq(&terms);
+    }
+
+    #[test]
+    fn toml_terms() {
+        let terms =
+            parse_toml("[terms]\nkubelet = \"the agent that runs on each node\"\n").unwrap();
+        assert_eq!(terms[0].term, "kubelet");
+        assert_eq!(terms[0].definition, "the agent that runs on each node");
+    }
+
+    #[test]
+    fn lookup() {
+        let mut glossary = Glossary::default();
+        glossary.extend(parse_markdown("**Control plane**: manages the cluster\n"));
+        assert_eq!(glossary.lookup("control_plane").len(), 1);
+        assert!(glossary.lookup("plane").is_empty());
+        let expected = expect![[r#"
+            **Control plane** _glossary_
+            manages the cluster"#]];
+        expected.assert_eq(
+            &glossary
+                .render(&["plane".to_owned(), "control_plane".to_owned()])
+                .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// Carrying the form of a word in a document over to a replacement for it, such as a synonym, so
+// that "Running" becomes "Sprinting" rather than "sprint".
+
+use wordnet_ls::wordnet::PartOfSpeech;
+
+/// Regular inflections of a base form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inflection {
+    /// Plural nouns and third person singular verbs.
+    S,
+    /// Past tense and participle.
+    Ed,
+    /// Present participle.
+    Ing,
+    Comparative,
+    Superlative,
+}
+
+impl Inflection {
+    /// How `word` is inflected from its base form `lemma`, if it is regularly.
+    pub fn between(word: &str, lemma: &str, pos: PartOfSpeech) -> Option<Self> {
+        let word = word.to_lowercase();
+        let lemma = lemma.to_lowercase();
+        if word == lemma {
+            return None;
+        }
+        let regular = [
+            Self::S,
+            Self::Ed,
+            Self::Ing,
+            Self::Comparative,
+            Self::Superlative,
+        ]
+        .into_iter()
+        .filter(|inflection| inflection.applies_to(pos))
+        .find(|inflection| {
+            inflection.apply(&lemma, pos, |_| None) == word || inflection.matches_suffix(&word)
+        });
+        // nouns only inflect for number, like "women", but irregular verbs, like "ran", could be
+        // in any tense
+        match pos {
+            PartOfSpeech::Noun => Some(Self::S),
+            _ => regular,
+        }
+    }
+
+    fn applies_to(self, pos: PartOfSpeech) -> bool {
+        match self {
+            Self::S => matches!(pos, PartOfSpeech::Noun | PartOfSpeech::Verb),
+            Self::Ed | Self::Ing => pos == PartOfSpeech::Verb,
+            Self::Comparative | Self::Superlative => pos == PartOfSpeech::Adjective,
+        }
+    }
+
+    fn matches_suffix(self, word: &str) -> bool {
+        match self {
+            Self::S => word.ends_with('s'),
+            Self::Ed => word.ends_with("ed"),
+            Self::Ing => word.ends_with("ing"),
+            Self::Comparative => word.ends_with("er"),
+            Self::Superlative => word.ends_with("est"),
+        }
+    }
+
+    /// Inflect a base form, which may be a collocation with underscores. Verbs inflect their first
+    /// word ("run_off" to "running_off") and the rest their last ("ice_cream" to "ice_creams").
+    ///
+    /// Irregular plurals of nouns, like "women", are given by `plural`.
+    pub fn apply(
+        self,
+        base: &str,
+        pos: PartOfSpeech,
+        plural: impl Fn(&str) -> Option<String>,
+    ) -> String {
+        let (before, word, after) = if pos == PartOfSpeech::Verb {
+            match base.split_once('_') {
+                Some((word, rest)) => ("", word, format!("_{rest}")),
+                None => ("", base, String::new()),
+            }
+        } else {
+            match base.rsplit_once('_') {
+                Some((rest, word)) => (rest, word, String::new()),
+                None => ("", base, String::new()),
+            }
+        };
+        let separator = if before.is_empty() { "" } else { "_" };
+        let inflected = match (self, pos) {
+            (Self::S, PartOfSpeech::Noun) => plural(word).unwrap_or_else(|| self.inflect(word)),
+            _ => self.inflect(word),
+        };
+        format!("{before}{separator}{inflected}{after}")
+    }
+
+    fn inflect(self, word: &str) -> String {
+        let chars = word.chars().collect::<Vec<_>>();
+        let ends_consonant_y =
+            word.ends_with('y') && chars.len() > 1 && !is_vowel(chars[chars.len() - 2]);
+        match self {
+            Self::S => {
+                if ["s", "x", "z", "ch", "sh"]
+                    .iter()
+                    .any(|s| word.ends_with(s))
+                {
+                    format!("{word}es")
+                } else if ends_consonant_y {
+                    format!("{}ies", &word[..word.len() - 1])
+                } else {
+                    format!("{word}s")
+                }
+            }
+            Self::Ing => {
+                if let Some(stem) = word.strip_suffix("ie") {
+                    format!("{stem}ying")
+                } else if word.ends_with('e') && !word.ends_with("ee") && chars.len() > 2 {
+                    format!("{}ing", &word[..word.len() - 1])
+                } else {
+                    format!("{}ing", double_final(word))
+                }
+            }
+            Self::Ed => suffixed(word, "ed", ends_consonant_y),
+            Self::Comparative => suffixed(word, "er", ends_consonant_y),
+            Self::Superlative => suffixed(word, "est", ends_consonant_y),
+        }
+    }
+}
+
+/// Add a suffix starting with `e`, as in "walked", "wider" and "happiest".
+fn suffixed(word: &str, suffix: &str, ends_consonant_y: bool) -> String {
+    if word.ends_with('e') {
+        format!("{word}{}", &suffix[1..])
+    } else if ends_consonant_y {
+        format!("{}i{suffix}", &word[..word.len() - 1])
+    } else {
+        format!("{}{suffix}", double_final(word))
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiou".contains(c)
+}
+
+/// Short words ending consonant, vowel, consonant double their last letter, as in "running".
+fn double_final(word: &str) -> String {
+    let chars = word.chars().collect::<Vec<_>>();
+    match chars.as_slice() {
+        [.., a, b, c]
+            if chars.iter().filter(|c| is_vowel(**c)).count() == 1
+                && !is_vowel(*a)
+                && is_vowel(*b)
+                && !is_vowel(*c)
+                && !"wxy".contains(*c) =>
+        {
+            format!("{word}{c}")
+        }
+        _ => word.to_owned(),
+    }
+}
+
+/// Give the replacement the casing of the original word, and spaces in place of underscores.
+pub fn match_case(original: &str, replacement: &str) -> String {
+    let replacement = replacement.replace('_', " ");
+    let mut chars = original.chars();
+    match chars.next() {
+        Some(first) if first.is_uppercase() => {
+            if chars.clone().count() > 0 && chars.all(|c| !c.is_lowercase()) {
+                replacement.to_uppercase()
+            } else {
+                let mut replacement = replacement.chars();
+                replacement
+                    .next()
+                    .map(|c| c.to_uppercase().chain(replacement).collect())
+                    .unwrap_or_default()
+            }
+        }
+        _ => replacement,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflect() {
+        let check = |inflection: Inflection, base: &str, pos: PartOfSpeech, expected: &str| {
+            assert_eq!(inflection.apply(base, pos, |_| None), expected);
+        };
+        check(Inflection::Ing, "sprint", PartOfSpeech::Verb, "sprinting");
+        check(Inflection::Ing, "run", PartOfSpeech::Verb, "running");
+        check(Inflection::Ing, "move", PartOfSpeech::Verb, "moving");
+        check(Inflection::Ing, "die", PartOfSpeech::Verb, "dying");
+        check(
+            Inflection::Ing,
+            "run_off",
+            PartOfSpeech::Verb,
+            "running_off",
+        );
+        check(Inflection::Ed, "stop", PartOfSpeech::Verb, "stopped");
+        check(Inflection::Ed, "travel", PartOfSpeech::Verb, "traveled");
+        check(Inflection::Ed, "carry", PartOfSpeech::Verb, "carried");
+        check(Inflection::Ed, "decease", PartOfSpeech::Verb, "deceased");
+        check(Inflection::S, "church", PartOfSpeech::Noun, "churches");
+        check(Inflection::S, "ice_cream", PartOfSpeech::Noun, "ice_creams");
+        check(Inflection::S, "tally", PartOfSpeech::Noun, "tallies");
+        check(Inflection::S, "day", PartOfSpeech::Noun, "days");
+        check(
+            Inflection::Comparative,
+            "big",
+            PartOfSpeech::Adjective,
+            "bigger",
+        );
+        check(
+            Inflection::Superlative,
+            "happy",
+            PartOfSpeech::Adjective,
+            "happiest",
+        );
+        let plural = |word: &str| (word == "woman").then(|| "women".to_owned());
+        assert_eq!(
+            Inflection::S.apply("black_woman", PartOfSpeech::Noun, plural),
+            "black_women"
+        );
+    }
+
+    #[test]
+    fn between() {
+        use PartOfSpeech::*;
+        assert_eq!(
+            Inflection::between("Running", "run", Verb),
+            Some(Inflection::Ing)
+        );
+        assert_eq!(
+            Inflection::between("runs", "run", Verb),
+            Some(Inflection::S)
+        );
+        assert_eq!(
+            Inflection::between("women", "woman", Noun),
+            Some(Inflection::S)
+        );
+        assert_eq!(Inflection::between("ran", "run", Verb), None);
+        assert_eq!(
+            Inflection::between("walked", "walk", Verb),
+            Some(Inflection::Ed)
+        );
+        assert_eq!(Inflection::between("run", "run", Verb), None);
+    }
+
+    #[test]
+    fn casing() {
+        assert_eq!(match_case("Running", "sprinting"), "Sprinting");
+        assert_eq!(match_case("RUN", "sprint"), "SPRINT");
+        assert_eq!(match_case("A", "one"), "One");
+        assert_eq!(match_case("ice", "water_ice"), "water ice");
+    }
+}
@@ -48,6 +48,10 @@ impl OpenFiles {
         }
     }
 
+    pub fn uris(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
     pub fn remove(&mut self, uri: &str) {
         self.files.remove(uri);
     }
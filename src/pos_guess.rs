@@ -0,0 +1,258 @@
+// Rule-based guessing of the part of speech of a word from the words just before it and its
+// suffix. It is deliberately simple, only meant to order the readings of a word sensibly, e.g. the
+// noun first for "the run" and the verb first for "they run".
+
+use serde::Deserialize;
+use serde::Serialize;
+use wordnet_ls::wordnet::PartOfSpeech;
+
+const DETERMINERS: &[&str] = &[
+    "a", "an", "another", "any", "each", "every", "her", "his", "its", "my", "no", "our", "some",
+    "that", "the", "their", "these", "this", "those", "your",
+];
+
+const PRONOUNS: &[&str] = &["i", "you", "he", "she", "it", "we", "they"];
+
+const AUXILIARIES: &[&str] = &[
+    "can", "could", "did", "do", "does", "don't", "doesn't", "didn't", "may", "might", "must",
+    "shall", "should", "will", "won't", "would",
+];
+
+const BE: &[&str] = &["am", "are", "be", "been", "being", "is", "was", "were"];
+
+const INTENSIFIERS: &[&str] = &["more", "most", "quite", "rather", "too", "very"];
+
+const SUFFIXES: &[(&str, &[PartOfSpeech])] = &[
+    ("ly", &[PartOfSpeech::Adverb, PartOfSpeech::Adjective]),
+    ("tion", &[PartOfSpeech::Noun]),
+    ("sion", &[PartOfSpeech::Noun]),
+    ("ness", &[PartOfSpeech::Noun]),
+    ("ment", &[PartOfSpeech::Noun]),
+    ("ity", &[PartOfSpeech::Noun]),
+    ("ism", &[PartOfSpeech::Noun]),
+    ("ize", &[PartOfSpeech::Verb]),
+    ("ise", &[PartOfSpeech::Verb]),
+    ("ify", &[PartOfSpeech::Verb]),
+    ("ous", &[PartOfSpeech::Adjective]),
+    ("ful", &[PartOfSpeech::Adjective]),
+    ("ive", &[PartOfSpeech::Adjective]),
+    ("able", &[PartOfSpeech::Adjective]),
+    ("ible", &[PartOfSpeech::Adjective]),
+    ("less", &[PartOfSpeech::Adjective]),
+];
+
+/// Serialized form of a guess, for carrying it through completion items.
+#[derive(Serialize, Deserialize)]
+struct GuessData {
+    likely: Vec<String>,
+    exclusive: bool,
+}
+
+/// Parts of speech a word most likely is, best first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PosGuess {
+    pub likely: Vec<PartOfSpeech>,
+    /// Whether the other parts of speech are ruled out, rather than just less likely.
+    pub exclusive: bool,
+}
+
+impl PosGuess {
+    /// Guess from the text on the line before the word and the word itself.
+    pub fn new(before: &str, word: &str) -> Self {
+        let previous = before
+            .split(|c: char| c.is_whitespace() || (c.is_ascii_punctuation() && c != '\''))
+            .rev()
+            .find(|w| !w.is_empty())
+            .map(|w| w.to_lowercase());
+        // punctuation between the words breaks up the phrase
+        let adjacent = before
+            .trim_end()
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '\'');
+
+        if let Some(previous) = previous.filter(|_| adjacent) {
+            let previous = previous.as_str();
+            if DETERMINERS.contains(&previous) {
+                return Self::exclusive(&[PartOfSpeech::Noun, PartOfSpeech::Adjective]);
+            }
+            if PRONOUNS.contains(&previous) || AUXILIARIES.contains(&previous) {
+                return Self::exclusive(&[PartOfSpeech::Verb, PartOfSpeech::Adverb]);
+            }
+            if INTENSIFIERS.contains(&previous) {
+                return Self::exclusive(&[PartOfSpeech::Adjective, PartOfSpeech::Adverb]);
+            }
+            if BE.contains(&previous) {
+                return Self {
+                    likely: vec![PartOfSpeech::Adjective, PartOfSpeech::Verb],
+                    exclusive: false,
+                };
+            }
+            // an infinitive, as in "to run", or a preposition, as in "went to school"
+            if previous == "to" {
+                return Self {
+                    likely: vec![PartOfSpeech::Verb, PartOfSpeech::Noun],
+                    exclusive: false,
+                };
+            }
+        }
+
+        let word = word.to_lowercase();
+        SUFFIXES
+            .iter()
+            .find(|(suffix, _)| word.len() > suffix.len() + 2 && word.ends_with(suffix))
+            .map(|(_, likely)| Self {
+                likely: likely.to_vec(),
+                exclusive: false,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(GuessData {
+            likely: self.likely.iter().map(|pos| pos.to_string()).collect(),
+            exclusive: self.exclusive,
+        })
+        .unwrap()
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Option<Self> {
+        let data = serde_json::from_value::<GuessData>(value).ok()?;
+        let likely = data
+            .likely
+            .iter()
+            .map(|name| PartOfSpeech::iter().find(|pos| pos.to_string() == *name))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            likely,
+            exclusive: data.exclusive,
+        })
+    }
+
+    fn exclusive(likely: &[PartOfSpeech]) -> Self {
+        Self {
+            likely: likely.to_vec(),
+            exclusive: true,
+        }
+    }
+
+    /// Parts of speech in the order to show them, leaving out ruled out ones unless no likely
+    /// ones are `available`.
+    pub fn order(&self, available: impl Fn(PartOfSpeech) -> bool) -> Vec<PartOfSpeech> {
+        let likely = self
+            .likely
+            .iter()
+            .copied()
+            .filter(|pos| available(*pos))
+            .collect::<Vec<_>>();
+        if self.exclusive && !likely.is_empty() {
+            return likely;
+        }
+        let rest = PartOfSpeech::iter().filter(|pos| !likely.contains(pos) && available(*pos));
+        likely.iter().copied().chain(rest).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(before: &str, word: &str, expected: Expect) {
+        let guess = PosGuess::new(before, word);
+        expected.assert_debug_eq(&guess.order(|_| true));
+    }
+
+    #[test]
+    fn determiner() {
+        check(
+            "go for the ",
+            "run",
+            expect![[r#"
+                [
+                    Noun,
+                    Adjective,
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn pronoun() {
+        check(
+            "They ",
+            "run",
+            expect![[r#"
+                [
+                    Verb,
+                    Adverb,
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn to() {
+        check(
+            "they went to ",
+            "school",
+            expect![[r#"
+                [
+                    Verb,
+                    Noun,
+                    Adjective,
+                    Adverb,
+                ]
+            "#]],
+        );
+        // the noun senses of "work" are kept after the verb ones
+        let guess = PosGuess::new("went to ", "work");
+        let order = guess.order(|pos| matches!(pos, PartOfSpeech::Noun | PartOfSpeech::Verb));
+        assert_eq!(order, vec![PartOfSpeech::Verb, PartOfSpeech::Noun]);
+    }
+
+    #[test]
+    fn punctuation_between() {
+        check(
+            "the end. ",
+            "run",
+            expect![[r#"
+                [
+                    Noun,
+                    Verb,
+                    Adjective,
+                    Adverb,
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn suffix() {
+        check(
+            "",
+            "quickly",
+            expect![[r#"
+                [
+                    Adverb,
+                    Adjective,
+                    Noun,
+                    Verb,
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let guess = PosGuess::new("they ", "run");
+        assert_eq!(PosGuess::from_value(guess.to_value()), Some(guess));
+    }
+
+    #[test]
+    fn ruled_out_unless_unavailable() {
+        let guess = PosGuess::new("the ", "quickly");
+        let order = guess.order(|pos| pos == PartOfSpeech::Adverb);
+        assert_eq!(order, vec![PartOfSpeech::Adverb]);
+    }
+}
@@ -0,0 +1,338 @@
+// Spell checking of documents against the dictionary. WordNet only has nouns, verbs, adjectives
+// and adverbs so the other, closed, classes of words are listed here.
+
+use std::collections::BTreeSet;
+
+/// Default for the shortest word that gets checked.
+pub const MIN_LENGTH: usize = 3;
+
+const DETERMINERS: &str =
+    "a an the this that these those each every either neither any some no none both all few \
+    many much more most several such what which whatever whichever whose own";
+
+const PRONOUNS: &str =
+    "i me my mine myself you your yours yourself yourselves he him his himself she her hers \
+    herself it its itself we us our ours ourselves they them their theirs themselves who \
+    whom whoever someone somebody something anyone anybody anything everyone everybody \
+    everything nobody nothing one ones";
+
+const PREPOSITIONS: &str =
+    "about above across after against along amid among amongst around as at before behind \
+    below beneath beside besides between beyond by despite down during except for from in \
+    inside into like near of off on onto out outside over past per since than through \
+    throughout till to toward towards under underneath until unto up upon via with within \
+    without";
+
+const CONJUNCTIONS: &str =
+    "and but or nor so yet if unless because although though while whilst whereas whether \
+    where when whenever wherever why how then once";
+
+const AUXILIARIES: &str =
+    "am is are was were be been being do does did done have has had having can could may \
+    might must shall should will would ought isn't aren't wasn't weren't don't doesn't \
+    didn't haven't hasn't hadn't can't cannot couldn't mightn't mustn't shan't shouldn't \
+    won't wouldn't i'm i've i'd i'll you're you've you'd you'll he's he'd he'll she's she'd \
+    she'll it's it'd it'll we're we've we'd we'll they're they've they'd they'll that's \
+    there's here's what's who's let's";
+
+const OTHERS: &str =
+    "not there here yes also too very just only even ever never again however therefore thus \
+    hence etc";
+
+/// Closed classes of words, which WordNet leaves out, as space separated lists.
+const FUNCTION_WORDS: &[(&str, &str)] = &[
+    ("determiner", DETERMINERS),
+    ("pronoun", PRONOUNS),
+    ("preposition", PREPOSITIONS),
+    ("conjunction", CONJUNCTIONS),
+    ("auxiliary", AUXILIARIES),
+    ("function word", OTHERS),
+];
+
+/// Words of the closed classes with the name of their class, e.g. `("the", "determiner")`.
+pub fn function_words() -> impl Iterator<Item = (&'static str, &'static str)> {
+    FUNCTION_WORDS
+        .iter()
+        .flat_map(|(class, words)| words.split_whitespace().map(move |word| (word, *class)))
+}
+
+fn is_function_word(word: &str) -> bool {
+    function_words().any(|(w, _)| w == word)
+}
+
+/// A word in a document that the dictionary does not know.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub line: usize,
+    /// Character offsets of the word in the line.
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+}
+
+#[derive(Debug)]
+pub struct SpellChecker {
+    min_length: usize,
+    /// Lowercased words to never report.
+    ignore: BTreeSet<String>,
+}
+
+impl Default for SpellChecker {
+    fn default() -> Self {
+        Self::new(MIN_LENGTH, &[])
+    }
+}
+
+impl SpellChecker {
+    pub fn new(min_length: usize, ignore: &[String]) -> Self {
+        Self {
+            min_length,
+            ignore: ignore.iter().map(|w| w.to_lowercase()).collect(),
+        }
+    }
+
+    /// Words in the content that are not `known`, which is given lowercased words.
+    pub fn check(&self, content: &str, known: impl Fn(&str) -> bool) -> Vec<Misspelling> {
+        let mut misspellings = Vec::new();
+        for (line, text) in content.lines().enumerate() {
+            for (start, word) in tokens(text) {
+                if self.skip(word) {
+                    continue;
+                }
+                let lower = word.to_lowercase();
+                let lower = lower.strip_suffix("'s").unwrap_or(&lower);
+                if is_function_word(lower) || known(lower) {
+                    continue;
+                }
+                // hyphenated compounds are fine if all their parts are
+                if lower.contains('-')
+                    && lower
+                        .split('-')
+                        .all(|part| part.is_empty() || is_function_word(part) || known(part))
+                {
+                    continue;
+                }
+                misspellings.push(Misspelling {
+                    line,
+                    start,
+                    end: start + word.chars().count(),
+                    word: word.to_owned(),
+                });
+            }
+        }
+        misspellings
+    }
+
+    /// Capitalised words are likely names and those with numbers identifiers, neither of which
+    /// would be in the dictionary.
+    fn skip(&self, word: &str) -> bool {
+        word.chars().count() < self.min_length
+            || word.chars().next().is_some_and(|c| c.is_uppercase())
+            || word.chars().any(|c| c.is_numeric())
+            || self.ignore.contains(&word.to_lowercase())
+    }
+}
+
+/// Furthest, in edits, that a suggestion can be from the misspelt word.
+const MAX_EDITS: usize = 2;
+
+/// Up to `limit` of the candidates closest to the word, closest first, with collocations spaced.
+pub fn suggestions<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a String>,
+    limit: usize,
+) -> Vec<(String, usize)> {
+    let word = word.to_lowercase().chars().collect::<Vec<_>>();
+    let mut found = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let chars = candidate.chars().collect::<Vec<_>>();
+            let distance = edit_distance(&word, &chars)?;
+            // typos rarely change the first letter
+            let first_differs = word.first() != chars.first();
+            Some((
+                (distance, first_differs, candidate.contains('_')),
+                candidate,
+            ))
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    found.dedup_by(|a, b| a.1 == b.1);
+    found
+        .into_iter()
+        .take(limit)
+        .map(|((distance, _, _), candidate)| (candidate.replace('_', " "), distance))
+        .collect()
+}
+
+/// Number of insertions, deletions, substitutions and transpositions of adjacent characters to
+/// turn one word into the other, if at most [`MAX_EDITS`].
+fn edit_distance(a: &[char], b: &[char]) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > MAX_EDITS {
+        return None;
+    }
+    // rows of the optimal string alignment distance matrix
+    let mut before = Vec::new();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        if current.iter().all(|d| *d > MAX_EDITS) {
+            return None;
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    Some(previous[b.len()]).filter(|d| *d <= MAX_EDITS)
+}
+
+/// Words in a line with their starting character offset, leaving out links and email addresses.
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '\'' || c == '-';
+    let mut tokens = Vec::new();
+    let mut chunk_start = None;
+    for (i, (offset, c)) in line.char_indices().chain([(line.len(), ' ')]).enumerate() {
+        match (chunk_start, c.is_whitespace()) {
+            (None, false) => chunk_start = Some((i, offset)),
+            (Some((start_char, start)), true) => {
+                chunk_start = None;
+                let chunk = &line[start..offset];
+                if chunk.contains("://") || chunk.contains('@') {
+                    continue;
+                }
+                let mut word_start = None;
+                for (j, (o, c)) in chunk.char_indices().chain([(chunk.len(), ' ')]).enumerate() {
+                    match (word_start, is_word_char(c)) {
+                        (None, true) => word_start = Some((j, o)),
+                        (Some((first, from)), false) => {
+                            word_start = None;
+                            let word = &chunk[from..o];
+                            let trimmed = word.trim_start_matches(['\'', '-']);
+                            let skipped = word.len() - trimmed.len();
+                            let trimmed = trimmed.trim_end_matches(['\'', '-']);
+                            if !trimmed.is_empty() {
+                                tokens.push((start_char + first + skipped, trimmed));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn tokenize() {
+        let expected = expect![[r#"
+            [
+                (
+                    0,
+                    "don't",
+                ),
+                (
+                    6,
+                    "well-known",
+                ),
+                (
+                    18,
+                    "quotes",
+                ),
+                (
+                    39,
+                    "café",
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&tokens(
+            "don't well-known 'quotes' https://x.io café me@x.io",
+        ));
+    }
+
+    #[test]
+    fn distance() {
+        let distance = |a: &str, b: &str| {
+            edit_distance(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(distance("walk", "walk"), Some(0));
+        assert_eq!(distance("wlak", "walk"), Some(1));
+        assert_eq!(distance("wlaks", "walk"), Some(2));
+        assert_eq!(distance("quikcly", "quickly"), Some(1));
+        assert_eq!(distance("wlaking", "walk"), None);
+        assert_eq!(distance("abcd", "wxyz"), None);
+    }
+
+    #[test]
+    fn suggest() {
+        let candidates = ["walk", "talk", "alk", "wall", "a_lot", "lot", "wasp"].map(String::from);
+        let expected = expect![[r#"
+            [
+                (
+                    "walk",
+                    1,
+                ),
+                (
+                    "wall",
+                    2,
+                ),
+                (
+                    "alk",
+                    2,
+                ),
+                (
+                    "talk",
+                    2,
+                ),
+            ]
+        "#]];
+        expected.assert_debug_eq(&suggestions("wlak", &candidates, 4));
+        assert_eq!(
+            suggestions("alot", &candidates, 1),
+            [("a lot".to_owned(), 1)]
+        );
+    }
+
+    #[test]
+    fn check() {
+        let known = ["walk", "quick", "fox", "well", "known"];
+        let checker = SpellChecker::new(3, &["teh".to_owned()]);
+        let misspellings = checker.check(
+            "The quikc fox's walk\nteh well-known Alice v2 ox\n  is quick-ish",
+            |w| known.contains(&w),
+        );
+        let expected = expect![[r#"
+            [
+                Misspelling {
+                    line: 0,
+                    start: 4,
+                    end: 9,
+                    word: "quikc",
+                },
+                Misspelling {
+                    line: 2,
+                    start: 5,
+                    end: 14,
+                    word: "quick-ish",
+                },
+            ]
+        "#]];
+        expected.assert_debug_eq(&misspellings);
+    }
+}
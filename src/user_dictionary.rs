@@ -0,0 +1,304 @@
+// Words added by the user on top of WordNet, such as project jargon, loaded from a TOML or JSON
+// file. Entries are turned into synsets so that they render like any other word.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+use wordnet_ls::wordnet::Lemma;
+use wordnet_ls::wordnet::PartOfSpeech;
+use wordnet_ls::wordnet::SemanticRelation;
+use wordnet_ls::wordnet::SemanticRelationship;
+use wordnet_ls::wordnet::SynSet;
+use wordnet_ls::wordnet::WordNet;
+
+/// Contents of a user dictionary file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UserDictionaryFile {
+    /// Words that are spelt correctly but have no entry.
+    #[serde(default)]
+    pub known_words: Vec<String>,
+    #[serde(default)]
+    pub words: Vec<UserWord>,
+}
+
+impl UserDictionaryFile {
+    fn read(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read user dictionary {path:?}: {e}"))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str::<Self>(&content)
+                .map_err(|e| format!("Invalid user dictionary {path:?}: {e}")),
+            _ => toml::from_str::<Self>(&content)
+                .map_err(|e| format!("Invalid user dictionary {path:?}: {e}")),
+        }
+    }
+
+    /// Add a known word to the dictionary file, creating it if needed.
+    ///
+    /// The file is edited in place, so the user's comments, formatting and ordering are kept.
+    pub fn add_known_word(path: &Path, word: &str) -> Result<(), String> {
+        let content = if path.exists() {
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read user dictionary {path:?}: {e}"))?
+        } else {
+            String::new()
+        };
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => with_known_word_json(&content, word),
+            _ => with_known_word_toml(&content, word),
+        }
+        .map_err(|e| format!("Invalid user dictionary {path:?}: {e}"))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {parent:?}: {e}"))?;
+        }
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write user dictionary {path:?}: {e}"))
+    }
+}
+
+/// TOML document with the word appended to its `known_words`, if missing.
+fn with_known_word_toml(content: &str, word: &str) -> Result<String, String> {
+    let mut document = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| e.to_string())?;
+    let known = document
+        .entry("known_words")
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or("known_words is not an array")?;
+    if !known.iter().any(|w| w.as_str() == Some(word)) {
+        known.push(word);
+    }
+    Ok(document.to_string())
+}
+
+/// JSON document with the word appended to its `known_words`, if missing, keeping the order of
+/// keys.
+fn with_known_word_json(content: &str, word: &str) -> Result<String, String> {
+    let mut document = if content.trim().is_empty() {
+        serde_json::Value::Object(serde_json::Map::new())
+    } else {
+        serde_json::from_str(content).map_err(|e| e.to_string())?
+    };
+    let known = document
+        .as_object_mut()
+        .ok_or("the dictionary is not an object")?
+        .entry("known_words")
+        .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or("known_words is not an array")?;
+    if !known.iter().any(|w| w.as_str() == Some(word)) {
+        known.push(word.into());
+    }
+    let mut content = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+    content.push('\n');
+    Ok(content)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserWord {
+    pub lemma: String,
+    /// One of noun, verb, adjective or adverb.
+    pub part_of_speech: String,
+    pub definition: String,
+    #[serde(default)]
+    pub examples: Vec<String>,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    /// WordNet words this is a kind of, as `word` for its first sense or `word#n` for the nth.
+    #[serde(default)]
+    pub hypernyms: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct UserDictionary {
+    /// Synsets for each lowercased lemma and synonym.
+    synsets: BTreeMap<String, Vec<SynSet>>,
+    /// Lowercased words without entries.
+    known: BTreeSet<String>,
+}
+
+impl UserDictionary {
+    /// Load the dictionary from a `.toml` or `.json` file, resolving hypernyms against WordNet.
+    pub fn load(path: &Path, wn: &WordNet) -> Result<Self, String> {
+        let file = UserDictionaryFile::read(path)?;
+        let mut dictionary = Self::new(file.words, wn)?;
+        for word in file.known_words {
+            dictionary.accept(&word);
+        }
+        Ok(dictionary)
+    }
+
+    pub fn new(words: Vec<UserWord>, wn: &WordNet) -> Result<Self, String> {
+        let mut dictionary = Self::default();
+        for word in words {
+            let part_of_speech = PartOfSpeech::iter()
+                .find(|pos| pos.to_string() == word.part_of_speech)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown part of speech {:?} for {:?}",
+                        word.part_of_speech, word.lemma
+                    )
+                })?;
+            let relationships = word
+                .hypernyms
+                .iter()
+                .map(|hypernym| {
+                    let (target, sense) = match hypernym.split_once('#') {
+                        Some((target, sense)) => (target, sense.parse::<usize>().ok()),
+                        None => (hypernym.as_str(), Some(1)),
+                    };
+                    let id = sense
+                        .and_then(|sense| sense.checked_sub(1))
+                        .and_then(|i| wn.synset_refs_for(target, part_of_speech).get(i).copied())
+                        .map(|ss| ss.id())
+                        .ok_or_else(|| {
+                            format!("Unknown hypernym {hypernym:?} for {:?}", word.lemma)
+                        })?;
+                    Ok(SemanticRelationship {
+                        relation: SemanticRelation::Hypernym,
+                        synset_offset: id.offset,
+                        part_of_speech: id.part_of_speech,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let lemmas = std::iter::once(&word.lemma)
+                .chain(&word.synonyms)
+                .map(|w| Lemma {
+                    word: w.replace(' ', "_"),
+                    part_of_speech,
+                    relationships: Vec::new(),
+                })
+                .collect::<Vec<_>>();
+            let synset = SynSet {
+                lemmas,
+                definition: word.definition,
+                examples: word.examples,
+                part_of_speech,
+                relationships,
+            };
+            for lemma in &synset.lemmas {
+                dictionary
+                    .synsets
+                    .entry(lemma.word.to_lowercase())
+                    .or_default()
+                    .push(synset.clone());
+            }
+        }
+        Ok(dictionary)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.synsets.contains_key(&word.to_lowercase())
+    }
+
+    /// Whether the word is in the dictionary, with or without an entry.
+    pub fn is_known(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.known.contains(&word) || self.synsets.contains_key(&word)
+    }
+
+    /// Treat the word as spelt correctly.
+    pub fn accept(&mut self, word: &str) {
+        self.known.insert(word.to_lowercase());
+    }
+
+    /// All lemmas, lowercased.
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        self.synsets.keys()
+    }
+
+    pub fn synsets_for(&self, word: &str, pos: PartOfSpeech) -> Vec<SynSet> {
+        self.synsets
+            .get(&word.to_lowercase())
+            .map(|synsets| {
+                synsets
+                    .iter()
+                    .filter(|ss| ss.part_of_speech == pos)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_toml() {
+        let file = toml::from_str::<UserDictionaryFile>(
+            r#"
+            [[words]]
+            lemma = "kubelet"
+            part_of_speech = "noun"
+            definition = "the agent that runs on each node of a Kubernetes cluster"
+            examples = ["the kubelet restarted the pod"]
+            synonyms = ["node agent"]
+            hypernyms = ["person#1"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.words.len(), 1);
+        assert_eq!(file.words[0].synonyms, ["node agent"]);
+        assert_eq!(file.words[0].hypernyms, ["person#1"]);
+    }
+
+    #[test]
+    fn parse_json() {
+        let file = serde_json::from_str::<UserDictionaryFile>(
+            r#"{"words": [{"lemma": "monorepo", "part_of_speech": "noun", "definition": "a single repository for many projects"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(file.words[0].lemma, "monorepo");
+        assert!(file.words[0].examples.is_empty());
+    }
+
+    #[test]
+    fn add_known_word() {
+        let path = std::env::temp_dir()
+            .join(format!("wordnet-ls-user-{}", std::process::id()))
+            .join("dictionary.toml");
+        UserDictionaryFile::add_known_word(&path, "kubelet").unwrap();
+        UserDictionaryFile::add_known_word(&path, "monorepo").unwrap();
+        UserDictionaryFile::add_known_word(&path, "kubelet").unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(content, "known_words = [\"kubelet\", \"monorepo\"]\n");
+    }
+
+    #[test]
+    fn add_known_word_keeps_comments() {
+        let dir = std::env::temp_dir().join(format!("wordnet-ls-user-edit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("dictionary.toml");
+        let original = "# project words\nknown_words = [\"kubelet\"] # jargon\n\n[[words]]\n# WordNet words this is a kind of\nlemma = \"monorepo\"\npart_of_speech = \"noun\"\ndefinition = \"a single repository\"\n";
+        std::fs::write(&path, original).unwrap();
+        UserDictionaryFile::add_known_word(&path, "kubectl").unwrap();
+        let toml = std::fs::read_to_string(&path).unwrap();
+
+        let path = dir.join("dictionary.json");
+        std::fs::write(&path, r#"{"words": [], "ignored": 1}"#).unwrap();
+        UserDictionaryFile::add_known_word(&path, "kubectl").unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            toml,
+            original.replace("[\"kubelet\"]", "[\"kubelet\", \"kubectl\"]")
+        );
+        let keys = serde_json::from_str::<serde_json::Value>(&json)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["words", "ignored", "known_words"]);
+    }
+}
@@ -1,22 +1,45 @@
+use cache::Cache;
 use data::Data;
+pub use graph::{Edge, Graph, Node, SynSetId};
 use index::Index;
+use index::IndexItem;
+pub use index::SenseCount;
+pub use lesk::Lesk;
 pub use pos::PartOfSpeech;
+pub use pos::PartsOfSpeech;
 use rayon::prelude::*;
 pub use relation::LexicalRelation;
+pub use relation::Relation;
 pub use relation::SemanticRelation;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+pub use synset::Lemma;
+pub use synset::SemanticRelationship;
 pub use synset::SynSet;
+pub use synset_cache::CacheStats;
+pub use synset_ref::SynSetRef;
+pub use tree::Tree;
 
 use self::lemmatize::Lemmatizer;
-use self::pos::PartsOfSpeech;
+use self::synset_cache::SynSetCache;
 
+mod cache;
 mod data;
+#[cfg(feature = "embedded")]
+mod embedded;
+pub mod export;
+mod file;
+mod graph;
 mod index;
 mod lemmatize;
+mod lesk;
 mod pos;
 mod relation;
 mod synset;
+mod synset_cache;
+mod synset_ref;
+mod tree;
 mod utils;
 
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +49,8 @@ pub enum Error {
         path: PathBuf,
         error: std::io::Error,
     },
+    #[error("Failed to read dictionary from bytes: {error}")]
+    Bytes { error: std::io::Error },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -35,6 +60,7 @@ pub struct WordNet {
     index: Index,
     data: Data,
     lemmatizer: Lemmatizer,
+    cache: Option<Cache>,
 }
 
 impl WordNet {
@@ -52,9 +78,66 @@ impl WordNet {
                 path: dir.to_owned(),
                 error: e,
             })?,
+            cache: None,
         })
     }
 
+    /// Like [`WordNet::new`] but reading the dictionary files from memory rather than mapping them
+    /// from a directory, given as pairs of file name (e.g. `index.noun`) and contents.
+    pub fn from_bytes(files: &[(&str, &'static [u8])]) -> Result<Self> {
+        Ok(Self {
+            index: Index::from_bytes(files).map_err(|error| Error::Bytes { error })?,
+            data: Data::from_bytes(files).map_err(|error| Error::Bytes { error })?,
+            lemmatizer: Lemmatizer::from_bytes(files).map_err(|error| Error::Bytes { error })?,
+            cache: None,
+        })
+    }
+
+    /// A dictionary without any words, for when no dictionary files are available.
+    pub fn empty() -> Self {
+        let names = ["index", "data"]
+            .iter()
+            .flat_map(|file| {
+                PartOfSpeech::iter().map(move |pos| format!("{file}.{}", pos.as_suffix()))
+            })
+            .chain(PartOfSpeech::iter().map(|pos| format!("{}.exc", pos.as_suffix())))
+            .collect::<Vec<_>>();
+        let files = names
+            .iter()
+            .map(|name| (name.as_str(), &[][..]))
+            .collect::<Vec<_>>();
+        Self::from_bytes(&files).unwrap()
+    }
+
+    /// Whether the directory looks like it holds a dictionary, i.e. has the index files.
+    pub fn is_dictionary(dir: &Path) -> bool {
+        PartOfSpeech::iter().all(|pos| Index::path(dir, pos).is_file())
+    }
+
+    /// Like [`WordNet::new`] but with lookups served from a compiled cache of the index files kept
+    /// in `cache_dir`, which is built on first use and rebuilt whenever the index files change.
+    pub fn with_cache(dir: &Path, cache_dir: &Path) -> Result<Self> {
+        let mut wn = Self::new(dir)?;
+        let cache = Cache::open(dir, cache_dir, &wn.index).map_err(|e| Error::IO {
+            path: cache_dir.to_owned(),
+            error: e,
+        })?;
+        wn.cache = Some(cache);
+        Ok(wn)
+    }
+
+    /// Keep up to `capacity` recently parsed synsets in memory, so that resolving the same synsets
+    /// repeatedly, such as when following relationships, does not re-read the data files.
+    pub fn with_synset_cache(mut self, capacity: usize) -> Self {
+        self.data.set_cache(SynSetCache::new(capacity));
+        self
+    }
+
+    /// Hit and miss counts of the synset cache, if enabled.
+    pub fn synset_cache_stats(&self) -> Option<CacheStats> {
+        self.data.cache_stats()
+    }
+
     pub fn contains(&self, word: &str) -> bool {
         PartOfSpeech::variants()
             .into_iter()
@@ -63,11 +146,20 @@ impl WordNet {
 
     /// Directly resolve a reference, this should only be used with part_of_speech, offset pairs
     /// from the returned results, such as the relationships in synsets.
-    pub fn resolve(&self, part_of_speech: PartOfSpeech, offset: u64) -> Option<SynSet> {
+    pub fn resolve(&self, part_of_speech: PartOfSpeech, offset: u64) -> Option<Arc<SynSet>> {
         self.data.load(offset, part_of_speech)
     }
 
+    /// Like [`WordNet::resolve`] but borrowing the synset from the data files rather than parsing it
+    /// into owned values.
+    pub fn resolve_ref(&self, part_of_speech: PartOfSpeech, offset: u64) -> Option<SynSetRef<'_>> {
+        self.data.load_ref(offset, part_of_speech)
+    }
+
     pub fn all_words(&self) -> Vec<String> {
+        if let Some(cache) = &self.cache {
+            return cache.words();
+        }
         let mut result = Vec::new();
         result.par_extend(
             PartOfSpeech::variants()
@@ -92,9 +184,26 @@ impl WordNet {
         self.lemmatizer.lemmatize(word, pos, &self.index)
     }
 
-    pub fn synsets(&self, word: &str) -> PartsOfSpeech<Vec<SynSet>> {
+    /// Irregular inflected forms of a base form, such as "women" for "woman", from the exception
+    /// lists.
+    pub fn inflections(&self, base: &str, pos: PartOfSpeech) -> Vec<String> {
+        self.lemmatizer.inflections(base, pos)
+    }
+
+    /// How many senses the word has in each part of speech, without loading them.
+    pub fn sense_counts(&self, word: &str) -> PartsOfSpeech<SenseCount> {
+        self.load_index(&word.to_lowercase()).map(|_pos, item| {
+            item.map(|item| SenseCount {
+                tagged: item.tagged_senses,
+                senses: item.syn_offsets.len(),
+            })
+            .unwrap_or_default()
+        })
+    }
+
+    pub fn synsets(&self, word: &str) -> PartsOfSpeech<Vec<Arc<SynSet>>> {
         let word = word.to_lowercase();
-        let items = self.index.load(&word);
+        let items = self.load_index(&word);
 
         items.map(|_pos, items| {
             items
@@ -108,9 +217,9 @@ impl WordNet {
         })
     }
 
-    pub fn synsets_for(&self, word: &str, pos: PartOfSpeech) -> Vec<SynSet> {
+    pub fn synsets_for(&self, word: &str, pos: PartOfSpeech) -> Vec<Arc<SynSet>> {
         let word = word.to_lowercase();
-        let item = self.index.load(&word);
+        let item = self.load_index(&word);
         let mut synsets = Vec::new();
 
         if let Some(item) = item.get(pos) {
@@ -124,11 +233,94 @@ impl WordNet {
 
         synsets
     }
+
+    /// Like [`WordNet::synsets_for`] but with the senses that use exactly the casing of `word`
+    /// first, e.g. "March" the month before "march" the walk.
+    pub fn synsets_for_cased(&self, word: &str, pos: PartOfSpeech) -> Vec<Arc<SynSet>> {
+        let mut synsets = self.synsets_for(word, pos);
+        synsets.sort_by_key(|ss| !ss.has_exact_lemma(word));
+        synsets
+    }
+
+    /// Stream every synset of a part of speech, in the order of the data file.
+    ///
+    /// Synsets are borrowed from the data file, use [`SynSetRef::to_synset`] for owned values.
+    pub fn synsets_iter(&self, pos: PartOfSpeech) -> impl Iterator<Item = SynSetRef<'_>> {
+        self.data.iter(pos)
+    }
+
+    /// Like [`WordNet::synsets_iter`] but processing the data file in parallel, in no particular
+    /// order.
+    pub fn par_synsets_iter(
+        &self,
+        pos: PartOfSpeech,
+    ) -> impl ParallelIterator<Item = SynSetRef<'_>> {
+        self.data.par_iter(pos)
+    }
+
+    /// Like [`WordNet::synsets_for`] but borrowing the synsets from the data files.
+    pub fn synset_refs_for(&self, word: &str, pos: PartOfSpeech) -> Vec<SynSetRef<'_>> {
+        let word = word.to_lowercase();
+        let item = self.load_index(&word);
+        match item.get(pos) {
+            Some(item) => item
+                .syn_offsets
+                .iter()
+                .filter_map(|offset| self.data.load_ref(*offset, item.pos))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Synsets within `depth` relations of the synset `id`, following only the given `relations`,
+    /// or all of them if empty. Lexical relations are resolved to the words they link.
+    pub fn neighbourhood(
+        &self,
+        id: SynSetId,
+        relations: &[Relation],
+        depth: usize,
+    ) -> Option<Graph> {
+        Graph::neighbourhood(self, id, relations, depth)
+    }
+
+    /// Hyponyms of the synset `id`, and theirs in turn, down to `depth` levels.
+    pub fn hyponym_tree(&self, id: SynSetId, depth: usize) -> Option<Tree> {
+        let relations = [SemanticRelation::Hyponym, SemanticRelation::InstanceHyponym];
+        Tree::build(self, id, &relations, depth)
+    }
+
+    /// Troponyms of the verb synset `id`, the particular ways of doing it, which WordNet records as
+    /// hyponyms of verbs, down to `depth` levels.
+    pub fn troponym_tree(&self, id: SynSetId, depth: usize) -> Option<Tree> {
+        Tree::build(self, id, &[SemanticRelation::Hyponym], depth)
+    }
+
+    /// Everything the verb synset `id` entails, transitively, e.g. snore entails sleep.
+    pub fn entailments(&self, id: SynSetId) -> Option<Tree> {
+        Tree::build(self, id, &[SemanticRelation::Entailment], usize::MAX)
+    }
+
+    /// Everything the verb synset `id` causes, transitively, e.g. kill causes die.
+    pub fn causes(&self, id: SynSetId) -> Option<Tree> {
+        Tree::build(self, id, &[SemanticRelation::Cause], usize::MAX)
+    }
+
+    fn load_index(&self, word: &str) -> PartsOfSpeech<Option<IndexItem>> {
+        match &self.cache {
+            // fall back to the index files should the cache have been damaged since it was opened
+            Some(cache) => cache.load(word).unwrap_or_else(|| self.index.load(word)),
+            None => self.index.load(word),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::BTreeMap, env, path::PathBuf};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        env,
+        path::PathBuf,
+    };
 
     use super::*;
     use expect_test::expect;
@@ -138,9 +330,11 @@ mod tests {
         let word = "run";
         let wndir = env::var("WNSEARCHDIR").unwrap();
         let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
-        let def = wn
-            .synsets(word)
-            .map(|_pos, ss| ss.into_iter().map(|ss| ss.definition).collect::<Vec<_>>());
+        let def = wn.synsets(word).map(|_pos, ss| {
+            ss.iter()
+                .map(|ss| ss.definition.clone())
+                .collect::<Vec<_>>()
+        });
         let expected = expect![[r#"
             PartsOfSpeech {
                 noun: [
@@ -218,7 +412,7 @@ mod tests {
         let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
         let def = wn
             .synsets(word)
-            .map(|_pos, ss| ss.into_iter().map(|ss| ss.examples).collect::<Vec<_>>());
+            .map(|_pos, ss| ss.iter().map(|ss| ss.examples.clone()).collect::<Vec<_>>());
         let expected = expect![[r#"
             PartsOfSpeech {
                 noun: [
@@ -1139,9 +1333,9 @@ mod tests {
         let syn = wn.synsets(word);
         let resolved_related = syn.map(|_pos, synsets| {
             synsets
-                .into_iter()
+                .iter()
                 .flat_map(|s| {
-                    s.relationships.into_iter().filter_map(|r| {
+                    s.relationships.iter().filter_map(|r| {
                         wn.resolve(r.part_of_speech, r.synset_offset)
                             .map(|s| (r.relation, s))
                     })
@@ -5644,7 +5838,7 @@ mod tests {
                 .into_iter()
                 .map(|ss| {
                     ss.lemmas
-                        .into_iter()
+                        .iter()
                         .map(|l| (l.word.clone(), l.antonyms(&wn)))
                         .collect::<Vec<_>>()
                 })
@@ -154807,6 +155001,463 @@ mod tests {
         expected.assert_debug_eq(&words);
     }
 
+    #[test]
+    fn cached_lookups() {
+        let wndir = PathBuf::from(env::var("WNSEARCHDIR").unwrap());
+        let cache_dir = env::temp_dir().join("wordnet-ls-test-cache");
+        let wn = WordNet::new(&wndir).unwrap();
+        let cached = WordNet::with_cache(&wndir, &cache_dir).unwrap();
+        assert_eq!(wn.all_words(), cached.all_words());
+        for word in ["woman", "run", "axes", "unknownword"] {
+            assert_eq!(
+                format!("{:?}", wn.synsets(word)),
+                format!("{:?}", cached.synsets(word))
+            );
+        }
+        // reopening uses the already built cache
+        let reopened = WordNet::with_cache(&wndir, &cache_dir).unwrap();
+        assert_eq!(wn.all_words(), reopened.all_words());
+    }
+
+    #[test]
+    fn corrupt_cache_rebuilt() {
+        let wndir = PathBuf::from(env::var("WNSEARCHDIR").unwrap());
+        let cache_dir = env::temp_dir().join("wordnet-ls-test-corrupt-cache");
+        let wn = WordNet::new(&wndir).unwrap();
+        drop(WordNet::with_cache(&wndir, &cache_dir).unwrap());
+        let built = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+
+        let offsets = built.join("offsets.bin");
+        let contents = std::fs::read(&offsets).unwrap();
+        std::fs::write(&offsets, &contents[..contents.len() / 2]).unwrap();
+        let reopened = WordNet::with_cache(&wndir, &cache_dir).unwrap();
+        for word in ["woman", "run", "zoom"] {
+            assert_eq!(
+                format!("{:?}", wn.synsets(word)),
+                format!("{:?}", reopened.synsets(word))
+            );
+        }
+        drop(reopened);
+        assert_eq!(std::fs::read(&offsets).unwrap(), contents);
+
+        // the same length goes unnoticed until an entry is looked up, which is read from the index
+        // files instead and has the cache rebuilt next time
+        std::fs::write(&offsets, vec![0xff; contents.len()]).unwrap();
+        let reopened = WordNet::with_cache(&wndir, &cache_dir).unwrap();
+        assert_eq!(
+            format!("{:?}", wn.synsets("woman")),
+            format!("{:?}", reopened.synsets("woman"))
+        );
+        drop(reopened);
+        drop(WordNet::with_cache(&wndir, &cache_dir).unwrap());
+        assert_eq!(std::fs::read(&offsets).unwrap(), contents);
+
+        std::fs::write(built.join("words.fst"), b"garbage").unwrap();
+        let reopened = WordNet::with_cache(&wndir, &cache_dir).unwrap();
+        assert_eq!(wn.all_words(), reopened.all_words());
+    }
+
+    #[test]
+    fn synset_cache() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir))
+            .unwrap()
+            .with_synset_cache(1);
+        let first = wn.synsets_for("woman", PartOfSpeech::Noun);
+        let second = wn.synsets_for("woman", PartOfSpeech::Noun);
+        assert_eq!(format!("{first:?}"), format!("{second:?}"));
+        let stats = wn.synset_cache_stats().unwrap();
+        let expected = expect![[r#"
+            CacheStats {
+                hits: 0,
+                misses: 8,
+                len: 1,
+                capacity: 1,
+            }
+        "#]];
+        expected.assert_debug_eq(&stats);
+
+        let hypernym = &first[0].with_relationship(SemanticRelation::Hypernym)[0];
+        let missed = wn.resolve(hypernym.part_of_speech, hypernym.synset_offset);
+        let hit = wn.resolve(hypernym.part_of_speech, hypernym.synset_offset);
+        // hits share the cached synset rather than copying it
+        assert!(Arc::ptr_eq(&missed.unwrap(), &hit.unwrap()));
+        let stats = wn.synset_cache_stats().unwrap();
+        let expected = expect![[r#"
+            CacheStats {
+                hits: 1,
+                misses: 9,
+                len: 1,
+                capacity: 1,
+            }
+        "#]];
+        expected.assert_debug_eq(&stats);
+    }
+
+    #[test]
+    fn synset_refs_match_owned() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        for word in ["woman", "run", "happy"] {
+            for pos in PartOfSpeech::iter() {
+                let owned = wn.synsets_for(word, pos);
+                let refs = wn.synset_refs_for(word, pos);
+                assert_eq!(owned.len(), refs.len());
+                for (ss, ss_ref) in owned.iter().zip(&refs) {
+                    assert_eq!(ss.part_of_speech, ss_ref.part_of_speech());
+                    assert_eq!(ss.synonyms(), ss_ref.lemmas().collect::<Vec<_>>());
+                    assert_eq!(ss.definition, ss_ref.definition());
+                    assert_eq!(ss.examples, ss_ref.examples().collect::<Vec<_>>());
+                    assert_eq!(
+                        format!("{:?}", ss.relationships),
+                        format!("{:?}", ss_ref.relationships().collect::<Vec<_>>())
+                    );
+                    let lexical = ss
+                        .lemmas
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(i, l)| l.relationships.iter().map(move |r| (i, r)))
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        format!("{lexical:?}"),
+                        format!("{:?}", ss_ref.lexical_relationships().collect::<Vec<_>>())
+                    );
+                    assert_eq!(
+                        format!("{ss:?}"),
+                        format!("{:?}", ss_ref.to_synset().unwrap())
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn synset_json() {
+        let line = "02086723 05 n 02 dog 0 domestic_dog 0 002 @ 02085998 n 0000 ! 10114209 n 0101 | a member of the genus Canis; \"the dog barked all night\"";
+        let synset = SynSet::from_parts(line.split_whitespace()).unwrap();
+        let json = serde_json::to_string_pretty(&synset).unwrap();
+        let expected = expect![[r#"
+            {
+              "lemmas": [
+                {
+                  "word": "dog",
+                  "part_of_speech": "noun",
+                  "relationships": [
+                    {
+                      "relation": "antonym",
+                      "synset_offset": 10114209,
+                      "part_of_speech": "noun",
+                      "target": 0
+                    }
+                  ]
+                },
+                {
+                  "word": "domestic_dog",
+                  "part_of_speech": "noun",
+                  "relationships": []
+                }
+              ],
+              "definition": "a member of the genus Canis",
+              "examples": [
+                "the dog barked all night"
+              ],
+              "part_of_speech": "noun",
+              "relationships": [
+                {
+                  "relation": "hypernym",
+                  "synset_offset": 2085998,
+                  "part_of_speech": "noun"
+                }
+              ]
+            }"#]];
+        expected.assert_eq(&json);
+        let roundtrip = serde_json::from_str::<SynSet>(&json).unwrap();
+        assert_eq!(format!("{synset:?}"), format!("{roundtrip:?}"));
+    }
+
+    #[test]
+    fn neighbourhood_woman() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let id = wn.synset_refs_for("woman", PartOfSpeech::Noun)[0].id();
+        let relations = [
+            Relation::Semantic(SemanticRelation::Hypernym),
+            Relation::Lexical(LexicalRelation::Antonym),
+        ];
+        let graph = wn.neighbourhood(id, &relations, 2).unwrap();
+        assert_eq!(graph.nodes[0].id, id);
+        assert!(graph.nodes.iter().all(|n| n.distance <= 2));
+        let mut ids = graph.nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), graph.nodes.len());
+        assert!(graph.edges.iter().all(|e| relations.contains(&e.relation)));
+        let antonyms = graph
+            .edges_from(0)
+            .filter_map(|e| e.words.clone())
+            .collect::<Vec<_>>();
+        assert!(antonyms.contains(&("woman".to_owned(), "man".to_owned())));
+
+        // antonyms point back at each other, which must not loop or give an edge each way
+        let graph = wn.neighbourhood(id, &relations[1..], 10).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(
+            graph.edges[0].words,
+            Some(("woman".to_owned(), "man".to_owned()))
+        );
+    }
+
+    #[test]
+    fn hyponym_tree_person() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let id = wn.synset_refs_for("person", PartOfSpeech::Noun)[0].id();
+        let tree = wn.hyponym_tree(id, 1).unwrap();
+        assert_eq!(tree.id, id);
+        assert!(tree.children.iter().all(|c| c.children.is_empty()));
+        let adult = tree
+            .children
+            .iter()
+            .find(|c| c.synset.lemma("adult").is_some())
+            .unwrap();
+        assert!(adult.descendants > 0);
+        assert!(tree.descendants > tree.children.len());
+
+        let deeper = wn.hyponym_tree(id, 2).unwrap();
+        assert_eq!(deeper.descendants, tree.descendants);
+        assert!(deeper.children.iter().any(|c| !c.children.is_empty()));
+        let relations = [SemanticRelation::Hyponym, SemanticRelation::InstanceHyponym];
+        assert_descendants(&wn, &deeper, &relations);
+    }
+
+    #[test]
+    fn cyclic_tree() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let id = wn.synset_refs_for("happy", PartOfSpeech::Adjective)[0].id();
+        // similar to points both ways between a head adjective and its satellites
+        let relations = [SemanticRelation::SimilarTo];
+        let tree = Tree::build(&wn, id, &relations, 3).unwrap();
+        assert!(tree.descendants > 0);
+        assert_descendants(&wn, &tree, &relations);
+    }
+
+    /// Check the descendants of every synset in the tree against walking its relations.
+    fn assert_descendants(wn: &WordNet, tree: &Tree, relations: &[SemanticRelation]) {
+        let mut seen = BTreeSet::from([tree.id]);
+        let mut stack = vec![tree.id];
+        while let Some(id) = stack.pop() {
+            let ss = wn.resolve_ref(id.part_of_speech, id.offset).unwrap();
+            for r in ss.relationships() {
+                let target = SynSetId {
+                    part_of_speech: r.part_of_speech,
+                    offset: r.synset_offset,
+                };
+                if relations.contains(&r.relation) && seen.insert(target) {
+                    stack.push(target);
+                }
+            }
+        }
+        assert_eq!(tree.descendants, seen.len() - 1);
+        for child in &tree.children {
+            assert_descendants(wn, child, relations);
+        }
+    }
+
+    #[test]
+    fn verb_chains() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let kill = wn.synset_refs_for("kill", PartOfSpeech::Verb)[0].id();
+        let causes = wn.causes(kill).unwrap();
+        assert!(causes
+            .chains()
+            .iter()
+            .any(|chain| chain[0].lemma("die").is_some()));
+
+        let walk = wn.synset_refs_for("walk", PartOfSpeech::Verb)[0].id();
+        let entailments = wn.entailments(walk).unwrap();
+        assert!(entailments
+            .chains()
+            .iter()
+            .any(|chain| chain[0].lemma("step").is_some()));
+
+        let run = wn.synset_refs_for("run", PartOfSpeech::Verb)[0].id();
+        let troponyms = wn.troponym_tree(run, 2).unwrap();
+        assert!(troponyms.height() <= 2);
+        assert!(troponyms
+            .children
+            .iter()
+            .any(|c| c.synset.lemma("jog").is_some()));
+    }
+
+    #[test]
+    fn lesk_run() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let synsets = wn.synsets_for("run", PartOfSpeech::Noun);
+        let definitions = synsets
+            .iter()
+            .map(|ss| ss.definition.clone())
+            .collect::<Vec<_>>();
+
+        let lesk = Lesk::new(&wn, &["The Yankees scored", "in the baseball game"]);
+        let ranked = lesk.rank(synsets.clone());
+        assert!(ranked[0].0.definition.contains("baseball"));
+        assert!(ranked[0].1 > ranked[1].1);
+
+        let lesk = Lesk::new(&wn, &[] as &[&str]);
+        let ranked = lesk.rank(synsets);
+        assert!(ranked.iter().all(|(_, score)| *score == 0));
+        let ranked = ranked
+            .into_iter()
+            .map(|(ss, _)| ss.definition.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, definitions);
+    }
+
+    #[test]
+    fn lesk_cached_signatures() {
+        let wndir = PathBuf::from(env::var("WNSEARCHDIR").unwrap());
+        let wn = WordNet::new(&wndir).unwrap();
+        let cached = WordNet::new(&wndir).unwrap().with_synset_cache(100);
+        let context = ["The Yankees scored", "in the baseball game"];
+        let synsets = wn.synsets_for("run", PartOfSpeech::Noun);
+        let scores = |wn: &WordNet| {
+            Lesk::new(wn, &context)
+                .rank(synsets.clone())
+                .into_iter()
+                .map(|(ss, score)| (ss.definition.clone(), score))
+                .collect::<Vec<_>>()
+        };
+        let expected = scores(&wn);
+        assert_eq!(scores(&cached), expected);
+        // the second time round the signatures come from the cache
+        assert_eq!(scores(&cached), expected);
+
+        // a single sense is not scored however well it fits
+        let baseball = expected[0].0.clone();
+        let single = synsets
+            .into_iter()
+            .filter(|ss| ss.definition == baseball)
+            .collect::<Vec<_>>();
+        let ranked = Lesk::new(&wn, &context).rank(single);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].1, 0);
+    }
+
+    #[test]
+    fn export_dot_hypernyms() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let mut out = Vec::new();
+        export::write_dot(&wn, "woman", &[SemanticRelation::Hypernym], 2, &mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.starts_with("digraph \"woman\" {\n"));
+        assert!(dot.ends_with("}\n"));
+        let edges = dot
+            .lines()
+            .filter(|l| l.contains(" -> "))
+            .collect::<Vec<_>>();
+        assert!(!edges.is_empty());
+        assert!(edges.iter().all(|l| l.ends_with("[label=\"hypernym\"];")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_json_lines() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let mut out = Vec::new();
+        export::write_json_lines(&wn, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let first = out.lines().next().unwrap();
+        // records are synsets in their serialized form, with the offset alongside
+        let record = serde_json::from_str::<serde_json::Value>(first).unwrap();
+        let synset = serde_json::from_str::<SynSet>(first).unwrap();
+        let resolved = wn
+            .resolve(synset.part_of_speech, record["offset"].as_u64().unwrap())
+            .unwrap();
+        assert_eq!(synset.definition, resolved.definition);
+        assert_eq!(synset.synonyms(), resolved.synonyms());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_json_lines_malformed() {
+        // two lemmas promised but only one given
+        let names = ["index", "data"]
+            .iter()
+            .flat_map(|file| {
+                PartOfSpeech::iter().map(move |pos| format!("{file}.{}", pos.as_suffix()))
+            })
+            .chain(PartOfSpeech::iter().map(|pos| format!("{}.exc", pos.as_suffix())))
+            .collect::<Vec<_>>();
+        let files = names
+            .iter()
+            .map(|name| match name.as_str() {
+                "data.noun" => (name.as_str(), &b"00001740 03 n 02 entity 0\n"[..]),
+                _ => (name.as_str(), &[][..]),
+            })
+            .collect::<Vec<_>>();
+        let wn = WordNet::from_bytes(&files).unwrap();
+        let error = export::write_json_lines(&wn, &mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("offset 1740"), "{error}");
+    }
+
+    #[test]
+    fn relation_from_str() {
+        for relation in SemanticRelation::variants() {
+            let name = relation.to_string();
+            assert_eq!(name.parse::<SemanticRelation>(), Ok(relation));
+            assert_eq!(
+                name.replace(' ', "_").parse::<SemanticRelation>(),
+                Ok(relation)
+            );
+        }
+        assert!("unknown".parse::<SemanticRelation>().is_err());
+    }
+
+    #[test]
+    fn synsets_iter() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        for pos in PartOfSpeech::iter() {
+            let mut offsets = Vec::new();
+            for ss in wn.synsets_iter(pos) {
+                let resolved = wn.resolve_ref(pos, ss.offset()).unwrap();
+                assert_eq!(ss.definition(), resolved.definition());
+                offsets.push(ss.offset());
+            }
+            assert!(!offsets.is_empty());
+            let mut par_offsets = wn
+                .par_synsets_iter(pos)
+                .map(|ss| ss.offset())
+                .collect::<Vec<_>>();
+            par_offsets.sort_unstable();
+            assert_eq!(offsets, par_offsets);
+        }
+        // every synset found through the index is seen when streaming
+        let streamed = PartOfSpeech::iter()
+            .flat_map(|pos| wn.synsets_iter(pos).map(move |ss| (pos, ss.offset())))
+            .collect::<std::collections::BTreeSet<_>>();
+        for word in ["woman", "run", "happy", "quickly"] {
+            for pos in PartOfSpeech::iter() {
+                for ss in wn.synset_refs_for(word, pos) {
+                    assert!(streamed.contains(&(pos, ss.offset())));
+                }
+            }
+        }
+    }
+
     #[test]
     fn missing_dir() {
         let wn = WordNet::new(&PathBuf::from("/"));
@@ -154824,4 +155475,87 @@ mod tests {
         "#]];
         expected.assert_debug_eq(&wn);
     }
+
+    #[test]
+    fn from_bytes() {
+        let wndir = PathBuf::from(env::var("WNSEARCHDIR").unwrap());
+        let names = ["index", "data"]
+            .iter()
+            .flat_map(|file| {
+                PartOfSpeech::iter().map(move |pos| format!("{file}.{}", pos.as_suffix()))
+            })
+            .chain(PartOfSpeech::iter().map(|pos| format!("{}.exc", pos.as_suffix())))
+            .collect::<Vec<_>>();
+        let files = names
+            .iter()
+            .map(|name| {
+                let bytes = std::fs::read(wndir.join(name)).unwrap();
+                (name.as_str(), &*Vec::leak(bytes))
+            })
+            .collect::<Vec<_>>();
+        let wn = WordNet::from_bytes(&files).unwrap();
+        let mapped = WordNet::new(&wndir).unwrap();
+        for word in ["woman", "run", "ran", "happy", "quickly"] {
+            assert_eq!(
+                format!("{:?}", wn.synsets(word)),
+                format!("{:?}", mapped.synsets(word))
+            );
+        }
+        assert_eq!(wn.all_words(), mapped.all_words());
+
+        let missing = WordNet::from_bytes(&files[1..]);
+        let expected = expect![[r#"
+            Err(
+                Bytes {
+                    error: Custom {
+                        kind: NotFound,
+                        error: "no dictionary file index.noun",
+                    },
+                },
+            )
+        "#]];
+        expected.assert_debug_eq(&missing);
+    }
+
+    #[test]
+    fn empty() {
+        let wn = WordNet::empty();
+        assert!(!wn.contains("woman"));
+        assert!(wn.all_words().is_empty());
+        assert!(wn.lemmatize("women").all(|lemmas| lemmas.is_empty()));
+
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        assert!(WordNet::is_dictionary(&PathBuf::from(wndir)));
+        assert!(!WordNet::is_dictionary(&PathBuf::from("/")));
+    }
+
+    #[test]
+    fn inflections() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        assert!(wn
+            .inflections("woman", PartOfSpeech::Noun)
+            .contains(&"women".to_owned()));
+        assert!(wn.inflections("woman", PartOfSpeech::Verb).is_empty());
+    }
+
+    #[test]
+    fn sense_counts() {
+        let wndir = env::var("WNSEARCHDIR").unwrap();
+        let wn = WordNet::new(&PathBuf::from(wndir)).unwrap();
+        let cache_dir =
+            std::env::temp_dir().join(format!("wordnet-ls-senses-{}", std::process::id()));
+        let cached =
+            WordNet::with_cache(&PathBuf::from(env::var("WNSEARCHDIR").unwrap()), &cache_dir)
+                .unwrap();
+        for wn in [wn, cached] {
+            let run = wn.sense_counts("Run");
+            assert!(run.get(PartOfSpeech::Noun).senses > 0);
+            assert!(run.get(PartOfSpeech::Verb).tagged > 0);
+            assert_eq!(*run.get(PartOfSpeech::Adverb), SenseCount::default());
+            // words from the concordance rank above those missing from it
+            assert!(wn.sense_counts("thea").get(PartOfSpeech::Noun) < run.get(PartOfSpeech::Noun));
+        }
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
 }
@@ -0,0 +1,214 @@
+// Compiled on-disk cache of the index files.
+//
+// Scanning and sorting every index file is slow, so the lemmas get compiled into an FST which maps
+// each lemma to its entry in a table of synset offsets. The cache records the modification times of
+// the index files it was built from and is rebuilt whenever they change, along with the lengths of
+// its own files so that one cut short is rebuilt too.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use fst::Streamer;
+use memmap::Mmap;
+
+use super::index::{Index, IndexItem};
+use super::pos::{PartOfSpeech, PartsOfSpeech};
+
+/// Bump whenever the layout of the cache files changes.
+const VERSION: u32 = 2;
+
+const WORDS_FILE: &str = "words.fst";
+const OFFSETS_FILE: &str = "offsets.bin";
+const SOURCES_FILE: &str = "sources";
+
+#[derive(Debug)]
+pub struct Cache {
+    /// Lemma to position of its entry in the offsets table.
+    words: fst::Map<Mmap>,
+    /// For each lemma: a little endian u16 count of synsets per part of speech, then a u16 count of
+    /// tagged senses per part of speech, followed by as many little endian u64 synset offsets as
+    /// there are synsets.
+    offsets: Mmap,
+    /// Where the cache files are, to have the cache rebuilt if an entry turns out to be corrupt.
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Open the cache for the dictionary in `dir`, building it within `cache_dir` if it is missing,
+    /// out of date or not the size it was built at.
+    pub fn open(dir: &Path, cache_dir: &Path, index: &Index) -> std::io::Result<Self> {
+        let cache_dir = cache_dir.join(Self::key(dir));
+        let sources = Self::sources(dir)?;
+        let recorded = fs::read_to_string(cache_dir.join(SOURCES_FILE)).unwrap_or_default();
+        if let Some(lengths) = recorded.strip_prefix(&sources) {
+            if let Some(cache) = Self::map(&cache_dir)
+                .ok()
+                .filter(|cache| cache.lengths() == lengths)
+            {
+                return Ok(cache);
+            }
+        }
+
+        Self::build(&cache_dir, index, &sources)?;
+        Self::map(&cache_dir)
+    }
+
+    fn map(cache_dir: &Path) -> std::io::Result<Self> {
+        let words = unsafe { Mmap::map(&File::open(cache_dir.join(WORDS_FILE))?)? };
+        let words = fst::Map::new(words).map_err(std::io::Error::other)?;
+        let offsets = unsafe { Mmap::map(&File::open(cache_dir.join(OFFSETS_FILE))?)? };
+        Ok(Self {
+            words,
+            offsets,
+            dir: cache_dir.to_owned(),
+        })
+    }
+
+    /// Sizes of the cache files, recorded after the sources when building.
+    fn lengths(&self) -> String {
+        Self::format_lengths(self.words.as_fst().as_bytes().len(), self.offsets.len())
+    }
+
+    fn format_lengths(words: usize, offsets: usize) -> String {
+        format!("{WORDS_FILE} {words}\n{OFFSETS_FILE} {offsets}\n")
+    }
+
+    /// All lemmas in the cache, in sorted order.
+    pub fn words(&self) -> Vec<String> {
+        let mut results = Vec::with_capacity(self.words.len());
+        let mut stream = self.words.keys();
+        while let Some(word) = stream.next() {
+            results.push(String::from_utf8_lossy(word).into_owned());
+        }
+        results
+    }
+
+    /// The index entries for `word`, or None if its entry in the cache is corrupt, in which case the
+    /// cache is rebuilt the next time it is opened.
+    pub fn load(&self, word: &str) -> Option<PartsOfSpeech<Option<IndexItem>>> {
+        let Some(start) = self.words.get(word) else {
+            return Some(PartsOfSpeech::with(|_| None));
+        };
+        let entry = usize::try_from(start)
+            .ok()
+            .and_then(|start| self.entry(start));
+        let Some((counts, tagged, offsets)) = entry else {
+            // no longer fresh, whatever the sources say
+            let _ = fs::remove_file(self.dir.join(SOURCES_FILE));
+            return None;
+        };
+        let mut counts = counts.into_iter();
+        let mut tagged = tagged.into_iter();
+        let mut offsets = offsets
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+        Some(PartsOfSpeech::with(|pos| {
+            let count = counts.next().unwrap_or_default();
+            let tagged_senses = tagged.next().unwrap_or_default();
+            let syn_offsets = offsets.by_ref().take(count).collect::<Vec<_>>();
+            (count > 0).then_some(IndexItem {
+                pos,
+                syn_offsets,
+                tagged_senses,
+            })
+        }))
+    }
+
+    /// The synset counts, tagged sense counts and synset offset bytes of the entry at `start` in
+    /// the offsets table, or None if it does not fit in the table.
+    fn entry(&self, start: usize) -> Option<(Vec<usize>, Vec<usize>, &[u8])> {
+        let parts = PartOfSpeech::variants().len();
+        let to_counts = |bytes: &[u8]| {
+            bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]) as usize)
+                .collect::<Vec<_>>()
+        };
+        let counts_end = start.checked_add(2 * parts)?;
+        let counts = to_counts(self.offsets.get(start..counts_end)?);
+        let tagged_end = counts_end + 2 * parts;
+        let tagged = to_counts(self.offsets.get(counts_end..tagged_end)?);
+        let offsets_end = tagged_end + 8 * counts.iter().sum::<usize>();
+        let offsets = self.offsets.get(tagged_end..offsets_end)?;
+        Some((counts, tagged, offsets))
+    }
+
+    /// Directory name for the cache of a particular dictionary directory.
+    fn key(dir: &Path) -> String {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+        let mut hasher = DefaultHasher::new();
+        dir.hash(&mut hasher);
+        hex::encode(hasher.finish().to_be_bytes())
+    }
+
+    /// Description of the files the cache is built from, used to detect when it is stale.
+    fn sources(dir: &Path) -> std::io::Result<String> {
+        let mut sources = format!("version {VERSION}\n");
+        for pos in PartOfSpeech::iter() {
+            let path = Index::path(dir, pos);
+            let modified = fs::metadata(&path)?.modified()?;
+            let modified = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+            sources.push_str(&format!(
+                "{} {}.{:09}\n",
+                path.display(),
+                modified.as_secs(),
+                modified.subsec_nanos()
+            ));
+        }
+        Ok(sources)
+    }
+
+    fn build(cache_dir: &Path, index: &Index, sources: &str) -> std::io::Result<()> {
+        let mut entries = BTreeMap::new();
+        for pos in PartOfSpeech::iter() {
+            for (word, item) in index.items_for(pos) {
+                *entries
+                    .entry(word)
+                    .or_insert_with(|| PartsOfSpeech::with(|_| (Vec::new(), 0)))
+                    .get_mut(pos) = (item.syn_offsets, item.tagged_senses);
+            }
+        }
+
+        let mut words = fst::MapBuilder::memory();
+        let mut offsets = Vec::new();
+        for (word, items) in entries {
+            words
+                .insert(&word, offsets.len() as u64)
+                .map_err(std::io::Error::other)?;
+            for pos in PartOfSpeech::iter() {
+                offsets.extend((items.get(pos).0.len() as u16).to_le_bytes());
+            }
+            for pos in PartOfSpeech::iter() {
+                offsets.extend((items.get(pos).1.min(u16::MAX as usize) as u16).to_le_bytes());
+            }
+            for pos in PartOfSpeech::iter() {
+                for offset in &items.get(pos).0 {
+                    offsets.extend(offset.to_le_bytes());
+                }
+            }
+        }
+        let words = words.into_inner().map_err(std::io::Error::other)?;
+
+        fs::create_dir_all(cache_dir)?;
+        write_atomic(&cache_dir.join(WORDS_FILE), &words)?;
+        write_atomic(&cache_dir.join(OFFSETS_FILE), &offsets)?;
+        // written last so that a partially built cache is never considered fresh
+        let lengths = Self::format_lengths(words.len(), offsets.len());
+        write_atomic(
+            &cache_dir.join(SOURCES_FILE),
+            format!("{sources}{lengths}").as_bytes(),
+        )
+    }
+}
+
+/// Write the file next to its destination and move it into place, so that other servers sharing
+/// the cache never see a partially written file.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
@@ -1,3 +1,5 @@
+use super::file;
+use super::file::FileData;
 use super::pos::PartOfSpeech;
 use super::pos::PartsOfSpeech;
 use super::relation::LexicalRelation;
@@ -6,24 +8,104 @@ use super::synset::Lemma;
 use super::synset::LexicalRelationship;
 use super::synset::SemanticRelationship;
 use super::synset::SynSet;
-use memmap::Mmap;
-use std::fs::File;
+use super::synset_cache::CacheStats;
+use super::synset_cache::SenseKey;
+use super::synset_cache::SynSetCache;
+use super::synset_ref::SynSetRef;
+use rayon::prelude::*;
+use std::collections::BTreeSet;
 use std::io::BufRead as _;
 use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Data {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<FileData>,
+    cache: Option<SynSetCache>,
 }
 
 impl Data {
     pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
-        Ok(Self { maps })
+        let maps = PartsOfSpeech::try_with(|pos| file::open(dir, &Self::names(pos)))?;
+        Ok(Self { maps, cache: None })
+    }
+
+    pub fn from_bytes(files: &[(&str, &'static [u8])]) -> std::io::Result<Self> {
+        let maps = PartsOfSpeech::try_with(|pos| file::from_bytes(files, &Self::names(pos)))?;
+        Ok(Self { maps, cache: None })
+    }
+
+    pub fn set_cache(&mut self, cache: SynSetCache) {
+        self.cache = Some(cache);
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|c| c.stats())
     }
 
     /// Load a synset from the given offset in a particular part of speech file.
-    pub(super) fn load(&self, offset: u64, pos: PartOfSpeech) -> Option<SynSet> {
+    pub(super) fn load(&self, offset: u64, pos: PartOfSpeech) -> Option<Arc<SynSet>> {
+        let Some(cache) = &self.cache else {
+            return self.parse(offset, pos).map(Arc::new);
+        };
+        if let Some(synset) = cache.get(pos, offset) {
+            return Some(synset);
+        }
+        let synset = Arc::new(self.parse(offset, pos)?);
+        cache.insert(pos, offset, synset.clone());
+        Some(synset)
+    }
+
+    /// The signature of a sense, built with `build` unless it is in the cache.
+    pub(super) fn signature(
+        &self,
+        key: SenseKey,
+        build: impl FnOnce() -> BTreeSet<String>,
+    ) -> Arc<BTreeSet<String>> {
+        let Some(cache) = &self.cache else {
+            return Arc::new(build());
+        };
+        if let Some(signature) = cache.signature(&key) {
+            return signature;
+        }
+        let signature = Arc::new(build());
+        cache.insert_signature(key, signature.clone());
+        signature
+    }
+
+    /// Borrow a synset from the given offset in a particular part of speech file without parsing it.
+    pub(super) fn load_ref(&self, offset: u64, pos: PartOfSpeech) -> Option<SynSetRef<'_>> {
+        let map = self.maps.get(pos);
+        let rest = map.get(offset as usize..)?;
+        let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        let line = std::str::from_utf8(&rest[..end]).ok()?;
+        SynSetRef::from_line(line)
+    }
+
+    /// Borrow every synset in a particular part of speech file, in file order.
+    pub(super) fn iter(&self, pos: PartOfSpeech) -> impl Iterator<Item = SynSetRef<'_>> {
+        let map = self.maps.get(pos);
+        map.split(|b| *b == b'\n')
+            // license part
+            .filter(|line| !line.starts_with(b"  "))
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .filter_map(SynSetRef::from_line)
+    }
+
+    /// Like [`Data::iter`] but splitting the file between threads.
+    pub(super) fn par_iter(
+        &self,
+        pos: PartOfSpeech,
+    ) -> impl ParallelIterator<Item = SynSetRef<'_>> {
+        let map = self.maps.get(pos);
+        map.par_split(|b| *b == b'\n')
+            // license part
+            .filter(|line| !line.starts_with(b"  "))
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .filter_map(SynSetRef::from_line)
+    }
+
+    fn parse(&self, offset: u64, pos: PartOfSpeech) -> Option<SynSet> {
         let map = self.maps.get(pos);
         let mut line = String::new();
         (&map[offset as usize..]).read_line(&mut line).ok()?;
@@ -31,9 +113,9 @@ impl Data {
         SynSet::from_parts(line.split_whitespace())
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join("data").with_extension(pos.as_suffix());
-        File::open(p)
+    fn names(pos: PartOfSpeech) -> [String; 2] {
+        let suffix = pos.as_suffix();
+        [format!("data.{suffix}"), format!("{suffix}.dat")]
     }
 }
 
@@ -0,0 +1,44 @@
+// Dictionary files embedded into the binary with the `embedded` feature, taken from the directory
+// in `WNSEARCHDIR` at build time.
+
+use super::{Result, WordNet};
+
+/// The directory to embed, which is required with the feature.
+macro_rules! dictionary_dir {
+    () => {
+        env!(
+            "WNSEARCHDIR",
+            "the embedded feature embeds the WordNet dictionary in the directory in `WNSEARCHDIR`, \
+             set it to the dict directory of a WordNet install when building, e.g. \
+             `WNSEARCHDIR=/usr/share/wordnet cargo build --features embedded`"
+        )
+    };
+}
+
+macro_rules! embed {
+    ($($name:literal),*) => {
+        &[$(($name, include_bytes!(concat!(dictionary_dir!(), "/", $name)))),*]
+    };
+}
+
+const FILES: &[(&str, &[u8])] = embed!(
+    "index.noun",
+    "index.verb",
+    "index.adj",
+    "index.adv",
+    "data.noun",
+    "data.verb",
+    "data.adj",
+    "data.adv",
+    "noun.exc",
+    "verb.exc",
+    "adj.exc",
+    "adv.exc"
+);
+
+impl WordNet {
+    /// The dictionary embedded into the binary.
+    pub fn embedded() -> Result<Self> {
+        Self::from_bytes(FILES)
+    }
+}
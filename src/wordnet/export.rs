@@ -0,0 +1,262 @@
+//! Export of the database to other formats.
+
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::io::Write;
+
+use super::relation::{LexicalRelation, SemanticRelation};
+use super::{PartOfSpeech, WordNet};
+
+/// A synset as written to JSON Lines: its serialized form with its offset added.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct JsonLinesRecord {
+    offset: u64,
+    #[serde(flatten)]
+    synset: super::SynSet,
+}
+
+/// Write every synset as a JSON object per line.
+///
+/// Objects are the serialized form of [`super::SynSet`], with the `offset` of the synset added so
+/// that relationships can be matched up. A synset that cannot be read is an error rather than
+/// being left out.
+#[cfg(feature = "serde")]
+pub fn write_json_lines(wn: &WordNet, out: &mut impl Write) -> std::io::Result<()> {
+    for pos in PartOfSpeech::iter() {
+        for ss in wn.synsets_iter(pos) {
+            let synset = ss.to_synset().ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("malformed {pos} synset at offset {}", ss.offset()),
+                )
+            })?;
+            let record = JsonLinesRecord {
+                offset: ss.offset(),
+                synset,
+            };
+            serde_json::to_writer(&mut *out, &record)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+const TURTLE_PREFIXES: &str = "@prefix ontolex: <http://www.w3.org/ns/lemon/ontolex#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix skos: <http://www.w3.org/2004/02/skos/core#> .
+@prefix wn: <https://globalwordnet.github.io/schemas/wn#> .
+";
+
+/// Write every synset as an RDF graph in Turtle, using the OntoLex-Lemon vocabulary with relations
+/// from the Global WordNet schema.
+///
+/// Synsets become `ontolex:LexicalConcept`s, each lemma of a synset an `ontolex:LexicalSense` of
+/// the `ontolex:LexicalEntry` for the word.
+pub fn write_turtle(wn: &WordNet, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "{TURTLE_PREFIXES}")?;
+    for pos in PartOfSpeech::iter() {
+        for ss in wn.synsets_iter(pos) {
+            let concept = synset_iri(ss.offset(), ss.part_of_speech());
+            writeln!(out, "{concept} a ontolex:LexicalConcept ;")?;
+            writeln!(out, "    wn:partOfSpeech wn:{} ;", ss.part_of_speech())?;
+            for r in ss.relationships() {
+                writeln!(
+                    out,
+                    "    wn:{} {} ;",
+                    gwn_semantic_relation(r.relation),
+                    synset_iri(r.synset_offset, r.part_of_speech)
+                )?;
+            }
+            for example in ss.examples() {
+                writeln!(out, "    wn:example {} ;", literal(example))?;
+            }
+            writeln!(out, "    skos:definition {} .", literal(ss.definition()))?;
+
+            let lemmas = ss.lemmas().collect::<Vec<_>>();
+            for (i, word) in lemmas.iter().enumerate() {
+                let entry = entry_iri(word, ss.part_of_speech());
+                let sense = sense_iri(word, ss.offset(), ss.part_of_speech());
+                writeln!(out, "\n{entry} a ontolex:LexicalEntry ;")?;
+                writeln!(out, "    rdfs:label {} ;", literal(&word.replace('_', " ")))?;
+                writeln!(out, "    ontolex:sense {sense} .")?;
+                writeln!(out, "\n{sense} a ontolex:LexicalSense ;")?;
+                for (_, r) in ss.lexical_relationships().filter(|(s, _)| *s == i) {
+                    let target = wn
+                        .resolve_ref(r.part_of_speech, r.synset_offset)
+                        .and_then(|t| t.lemmas().nth(r.target));
+                    if let Some(target) = target {
+                        writeln!(
+                            out,
+                            "    wn:{} {} ;",
+                            gwn_lexical_relation(r.relation),
+                            sense_iri(target, r.synset_offset, r.part_of_speech)
+                        )?;
+                    }
+                }
+                writeln!(out, "    ontolex:isLexicalizedSenseOf {concept} .")?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a GraphViz digraph of the synsets reachable from the senses of `word` within `depth` steps
+/// along the given relations, or along all relations if none are given.
+pub fn write_dot(
+    wn: &WordNet,
+    word: &str,
+    relations: &[SemanticRelation],
+    depth: usize,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut queue = VecDeque::new();
+    let mut seen = BTreeSet::new();
+    let mut edges = BTreeSet::new();
+    for pos in PartOfSpeech::iter() {
+        for ss in wn.synset_refs_for(word, pos) {
+            if seen.insert((ss.part_of_speech(), ss.offset())) {
+                queue.push_back((ss, 0));
+            }
+        }
+    }
+
+    writeln!(out, "digraph {} {{", dot_id(word))?;
+    writeln!(out, "    node [shape=box];")?;
+    while let Some((ss, distance)) = queue.pop_front() {
+        let id = dot_id(&format!("{}-{}", ss.offset(), ss.part_of_speech()));
+        let label = format!(
+            "{}\\n({})",
+            ss.lemmas().collect::<Vec<_>>().join(", ").replace('_', " "),
+            ss.part_of_speech()
+        );
+        let style = if distance == 0 { ", style=bold" } else { "" };
+        writeln!(out, "    {id} [label={}{style}];", dot_id(&label))?;
+        if distance == depth {
+            continue;
+        }
+        for r in ss.relationships() {
+            if !relations.is_empty() && !relations.contains(&r.relation) {
+                continue;
+            }
+            let Some(target) = wn.resolve_ref(r.part_of_speech, r.synset_offset) else {
+                continue;
+            };
+            // symmetric relations, like similar to, point both ways but get a single edge
+            let (from, to) = (ss.id().min(target.id()), ss.id().max(target.id()));
+            if !edges.insert((from, to, r.relation)) {
+                continue;
+            }
+            let target_id = dot_id(&format!("{}-{}", target.offset(), target.part_of_speech()));
+            writeln!(
+                out,
+                "    {id} -> {target_id} [label={}];",
+                dot_id(&r.relation.to_string())
+            )?;
+            if seen.insert((target.part_of_speech(), target.offset())) {
+                queue.push_back((target, distance + 1));
+            }
+        }
+    }
+    writeln!(out, "}}")
+}
+
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+fn literal(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"@en")
+}
+
+fn pos_letter(pos: PartOfSpeech) -> char {
+    match pos {
+        PartOfSpeech::Noun => 'n',
+        PartOfSpeech::Verb => 'v',
+        PartOfSpeech::Adjective => 'a',
+        PartOfSpeech::Adverb => 'r',
+    }
+}
+
+/// Percent encode anything that might not be allowed in an IRI.
+fn iri_encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"_-.".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+fn synset_iri(offset: u64, pos: PartOfSpeech) -> String {
+    format!("<urn:wordnet-ls:synset:{offset:08}-{}>", pos_letter(pos))
+}
+
+fn entry_iri(word: &str, pos: PartOfSpeech) -> String {
+    format!(
+        "<urn:wordnet-ls:entry:{}-{}>",
+        iri_encode(word),
+        pos_letter(pos)
+    )
+}
+
+fn sense_iri(word: &str, offset: u64, pos: PartOfSpeech) -> String {
+    format!(
+        "<urn:wordnet-ls:sense:{}-{offset:08}-{}>",
+        iri_encode(word),
+        pos_letter(pos)
+    )
+}
+
+fn gwn_semantic_relation(relation: SemanticRelation) -> &'static str {
+    match relation {
+        SemanticRelation::Hypernym => "hypernym",
+        SemanticRelation::InstanceHypernym => "instance_hypernym",
+        SemanticRelation::Hyponym => "hyponym",
+        SemanticRelation::InstanceHyponym => "instance_hyponym",
+        SemanticRelation::MemberHolonym => "holo_member",
+        SemanticRelation::SubstanceHolonym => "holo_substance",
+        SemanticRelation::PartHolonym => "holo_part",
+        SemanticRelation::MemberMeronym => "mero_member",
+        SemanticRelation::SubstanceMeronym => "mero_substance",
+        SemanticRelation::PartMeronym => "mero_part",
+        SemanticRelation::Attribute => "attribute",
+        SemanticRelation::DomainOfSynsetTopic => "domain_topic",
+        SemanticRelation::MemberOfThisDomainTopic => "has_domain_topic",
+        SemanticRelation::DomainOfSynsetRegion => "domain_region",
+        SemanticRelation::MemberOfThisDomainRegion => "has_domain_region",
+        SemanticRelation::DomainOfSynsetUsage => "exemplifies",
+        SemanticRelation::MemberOfThisDomainUsage => "is_exemplified_by",
+        SemanticRelation::Entailment => "entails",
+        SemanticRelation::Cause => "causes",
+        SemanticRelation::VerbGroup => "similar",
+        SemanticRelation::SimilarTo => "similar",
+        SemanticRelation::DerivedFromAdjective => "pertainym",
+        SemanticRelation::AlsoSee => "also",
+    }
+}
+
+fn gwn_lexical_relation(relation: LexicalRelation) -> &'static str {
+    match relation {
+        LexicalRelation::Antonym => "antonym",
+        LexicalRelation::DerivationallyRelatedForm => "derivation",
+        LexicalRelation::AlsoSee => "also",
+        LexicalRelation::ParticipleOfVerb => "participle",
+        LexicalRelation::Pertainym => "pertainym",
+        LexicalRelation::DomainOfSynsetUsage => "exemplifies",
+        LexicalRelation::DomainOfSynsetRegion => "domain_region",
+        LexicalRelation::MemberOfThisDomainRegion => "has_domain_region",
+        LexicalRelation::MemberOfThisDomainUsage => "is_exemplified_by",
+        LexicalRelation::VerbGroup => "similar",
+        LexicalRelation::MemberOfThisDomainTopic => "has_domain_topic",
+        LexicalRelation::DomainOfSynsetTopic => "domain_topic",
+    }
+}
@@ -0,0 +1,123 @@
+// Opening the dictionary files, which may be laid out as in the Unix distribution (`index.noun`,
+// `data.noun`) or the Windows one (`noun.idx`, `noun.dat`), and may be gzipped.
+
+use flate2::read::GzDecoder;
+use memmap::Mmap;
+use std::fs::File;
+use std::io::Read as _;
+use std::ops::Deref;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Contents of a dictionary file.
+#[derive(Debug)]
+pub enum FileData {
+    Mapped(Mmap),
+    /// Gzipped files are decompressed into memory.
+    Decompressed(Vec<u8>),
+    /// Files given directly, such as ones embedded in the binary.
+    Static(&'static [u8]),
+}
+
+impl Deref for FileData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileData::Mapped(map) => map,
+            FileData::Decompressed(bytes) => bytes,
+            FileData::Static(bytes) => bytes,
+        }
+    }
+}
+
+/// The first of the file names that exists in the directory, as is or with a `.gz` suffix.
+///
+/// Defaults to the first name so that errors mention the usual layout.
+pub fn find(dir: &Path, names: &[String]) -> PathBuf {
+    names
+        .iter()
+        .flat_map(|name| [dir.join(name), dir.join(format!("{name}.gz"))])
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| dir.join(&names[0]))
+}
+
+/// Open the first of the file names found in the directory, see [`find`].
+pub fn open(dir: &Path, names: &[String]) -> std::io::Result<FileData> {
+    let path = find(dir, names);
+    let file = File::open(&path)?;
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let mut bytes = Vec::new();
+        GzDecoder::new(file).read_to_end(&mut bytes)?;
+        Ok(FileData::Decompressed(bytes))
+    } else {
+        Ok(FileData::Mapped(unsafe { Mmap::map(&file)? }))
+    }
+}
+
+/// Like [`open`] but picking from files given by name rather than from a directory.
+pub fn from_bytes(files: &[(&str, &'static [u8])], names: &[String]) -> std::io::Result<FileData> {
+    let found = names.iter().find_map(|name| {
+        let gzipped = format!("{name}.gz");
+        files
+            .iter()
+            .find(|(file, _)| file == name || *file == gzipped)
+    });
+    let Some((name, bytes)) = found else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no dictionary file {}", names[0]),
+        ));
+    };
+    if name.ends_with(".gz") {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(*bytes).read_to_end(&mut decompressed)?;
+        Ok(FileData::Decompressed(decompressed))
+    } else {
+        Ok(FileData::Static(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write as _;
+
+    #[test]
+    fn gzipped_alternate_layout() {
+        let dir = std::env::temp_dir().join(format!("wordnet-ls-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut encoder = GzEncoder::new(
+            File::create(dir.join("noun.idx.gz")).unwrap(),
+            Compression::default(),
+        );
+        encoder.write_all(b"word n 1 0 1 0 00000001\n").unwrap();
+        encoder.finish().unwrap();
+
+        let names = ["index.noun".to_owned(), "noun.idx".to_owned()];
+        assert_eq!(find(&dir, &names), dir.join("noun.idx.gz"));
+        let data = open(&dir, &names).unwrap();
+        assert!(matches!(data, FileData::Decompressed(_)));
+        assert_eq!(&*data, b"word n 1 0 1 0 00000001\n");
+
+        // uncompressed files in the usual layout are preferred
+        std::fs::write(dir.join("index.noun"), b"other n 1 0 1 0 00000002\n").unwrap();
+        assert_eq!(find(&dir, &names), dir.join("index.noun"));
+        assert!(matches!(open(&dir, &names).unwrap(), FileData::Mapped(_)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(find(&dir, &names), dir.join("index.noun"));
+    }
+
+    #[test]
+    fn bytes() {
+        let names = ["index.noun".to_owned(), "noun.idx".to_owned()];
+        let data = from_bytes(&[("noun.idx", b"word n 1 0 1 0 00000001\n")], &names).unwrap();
+        assert!(matches!(data, FileData::Static(_)));
+        assert_eq!(&*data, b"word n 1 0 1 0 00000001\n");
+        let err = from_bytes(&[("data.noun", b"")], &names).unwrap_err();
+        assert_eq!(err.to_string(), "no dictionary file index.noun");
+    }
+}
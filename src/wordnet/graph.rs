@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use super::pos::PartOfSpeech;
+use super::relation::Relation;
+use super::synset::SynSet;
+use super::WordNet;
+
+/// Location of a synset in the data files, identifying it uniquely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SynSetId {
+    /// File to look in.
+    pub part_of_speech: PartOfSpeech,
+    /// Offset in data file for the part of speech.
+    pub offset: u64,
+}
+
+/// Synsets around a starting synset and the relations between them.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    /// Synsets in the order they were reached, starting with the synset the graph was built from.
+    pub nodes: Vec<Node>,
+    /// Relations between the nodes, each relation between two synsets appearing once, so a
+    /// symmetric relation like antonymy gives one edge rather than one each way.
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    pub id: SynSetId,
+    pub synset: Arc<SynSet>,
+    /// Number of relations followed to reach the synset.
+    pub distance: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    /// Index of the node the relation is from.
+    pub source: usize,
+    /// Index of the node the relation is to.
+    pub target: usize,
+    pub relation: Relation,
+    /// For lexical relations, the source and target words.
+    pub words: Option<(String, String)>,
+}
+
+impl Graph {
+    /// Breadth first search from `id` along `relations`, or all relations if none are given, up to
+    /// `depth` steps away. Each synset is only visited once so cycles in the relations terminate.
+    pub(super) fn neighbourhood(
+        wn: &WordNet,
+        id: SynSetId,
+        relations: &[Relation],
+        depth: usize,
+    ) -> Option<Self> {
+        let synset = wn.resolve(id.part_of_speech, id.offset)?;
+        let mut graph = Graph {
+            nodes: vec![Node {
+                id,
+                synset,
+                distance: 0,
+            }],
+            edges: Vec::new(),
+        };
+        let mut indices = BTreeMap::from([(id, 0)]);
+        let mut seen_edges = BTreeSet::new();
+        let mut queue = VecDeque::from([0]);
+        let follow = |relation: Relation| relations.is_empty() || relations.contains(&relation);
+
+        while let Some(source) = queue.pop_front() {
+            let node = &graph.nodes[source];
+            if node.distance == depth {
+                continue;
+            }
+            let distance = node.distance + 1;
+
+            let mut targets = Vec::new();
+            for r in &node.synset.relationships {
+                let relation = Relation::Semantic(r.relation);
+                if follow(relation) {
+                    targets.push((relation, r.part_of_speech, r.synset_offset, None));
+                }
+            }
+            for lemma in &node.synset.lemmas {
+                for r in &lemma.relationships {
+                    let relation = Relation::Lexical(r.relation);
+                    if follow(relation) {
+                        let words = Some((lemma.word.clone(), r.target));
+                        targets.push((relation, r.part_of_speech, r.synset_offset, words));
+                    }
+                }
+            }
+
+            for (relation, part_of_speech, offset, words) in targets {
+                let id = SynSetId {
+                    part_of_speech,
+                    offset,
+                };
+                let target = match indices.get(&id) {
+                    Some(target) => *target,
+                    None => {
+                        let Some(synset) = wn.resolve(part_of_speech, offset) else {
+                            continue;
+                        };
+                        graph.nodes.push(Node {
+                            id,
+                            synset,
+                            distance,
+                        });
+                        let target = graph.nodes.len() - 1;
+                        indices.insert(id, target);
+                        queue.push_back(target);
+                        target
+                    }
+                };
+                // lexical relations point at a lemma of the target synset
+                let words = match words {
+                    Some((word, index)) => match graph.nodes[target].synset.lemmas.get(index) {
+                        Some(lemma) => Some((word, lemma.word.clone())),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                // a symmetric pointer is reached from both of its ends
+                let key = if source <= target {
+                    (source, target, relation, words.clone())
+                } else {
+                    (target, source, relation, words.clone().map(|(a, b)| (b, a)))
+                };
+                if !seen_edges.insert(key) {
+                    continue;
+                }
+                graph.edges.push(Edge {
+                    source,
+                    target,
+                    relation,
+                    words,
+                });
+            }
+        }
+        Some(graph)
+    }
+
+    /// Edges from the node at `index`.
+    pub fn edges_from(&self, index: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.source == index)
+    }
+}
@@ -1,25 +1,40 @@
-use memmap::Mmap;
-
+use super::file;
+use super::file::FileData;
 use super::pos::{PartOfSpeech, PartsOfSpeech};
 use super::utils;
-use std::fs::File;
 use std::io::BufRead;
 use std::path::Path;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct Index {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<FileData>,
 }
 
 #[derive(Debug)]
 pub struct IndexItem {
     pub pos: PartOfSpeech,
     pub syn_offsets: Vec<u64>,
+    /// Number of the senses that were tagged in the semantic concordance texts.
+    pub tagged_senses: usize,
+}
+
+/// How many senses a word has in a part of speech, ordered by how common that makes the word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SenseCount {
+    /// Senses seen in the semantic concordance texts, which only covers the more common words.
+    pub tagged: usize,
+    pub senses: usize,
 }
 
 impl Index {
     pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
+        let maps = PartsOfSpeech::try_with(|pos| file::open(dir, &Self::names(pos)))?;
+        Ok(Index { maps })
+    }
+
+    pub fn from_bytes(files: &[(&str, &'static [u8])]) -> std::io::Result<Self> {
+        let maps = PartsOfSpeech::try_with(|pos| file::from_bytes(files, &Self::names(pos)))?;
         Ok(Index { maps })
     }
 
@@ -31,9 +46,14 @@ impl Index {
         self.search(pos, word).is_some()
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join("index").with_extension(pos.as_suffix());
-        File::open(p)
+    /// Path of the index file in use for a part of speech.
+    pub fn path(dir: &Path, pos: PartOfSpeech) -> PathBuf {
+        file::find(dir, &Self::names(pos))
+    }
+
+    fn names(pos: PartOfSpeech) -> [String; 2] {
+        let suffix = pos.as_suffix();
+        [format!("index.{suffix}"), format!("{suffix}.idx")]
     }
 
     fn search(&self, pos: PartOfSpeech, word: &str) -> Option<IndexItem> {
@@ -66,6 +86,29 @@ impl Index {
         results.sort_unstable();
         results
     }
+
+    /// All parsed entries for a part of speech, in file order.
+    pub fn items_for(&self, pos: PartOfSpeech) -> Vec<(String, IndexItem)> {
+        let map = self.maps.get(pos);
+        let mut results = Vec::new();
+        for l in map.lines() {
+            match l {
+                Err(_) => continue,
+                Ok(l) => {
+                    if l.starts_with("  ") {
+                        // license part
+                        continue;
+                    }
+                    let lemma = l.split_whitespace().next();
+                    let item = IndexItem::try_from_parts(l.split_whitespace());
+                    if let (Some(lemma), Some(item)) = (lemma, item) {
+                        results.push((lemma.to_owned(), item));
+                    }
+                }
+            }
+        }
+        results
+    }
 }
 
 impl IndexItem {
@@ -78,12 +121,13 @@ impl IndexItem {
         let p_cnt = p_cnt.parse::<usize>().ok()?;
         let mut ps = ps.skip(p_cnt);
         let _sens_cnt = ps.next()?;
-        let _tagsense_cnt = ps.next()?;
+        let tagged_senses = ps.next()?.parse().ok()?;
         let syn_offsets: Option<Vec<_>> = ps.map(|x| x.parse().ok()).collect();
         let syn_offsets = syn_offsets?;
         Some(Self {
             pos: PartOfSpeech::try_from_str(pos)?,
             syn_offsets,
+            tagged_senses,
         })
     }
 }
@@ -1,25 +1,54 @@
 // https://wordnet.princeton.edu/documentation/morphy7wn
 
-use std::{fs::File, path::Path};
+use std::collections::BTreeMap;
+use std::path::Path;
 
-use memmap::Mmap;
-
-use super::{index::Index, pos::PartsOfSpeech, utils, PartOfSpeech};
+use super::{file, file::FileData, index::Index, pos::PartsOfSpeech, utils, PartOfSpeech};
 
 #[derive(Debug)]
 pub struct Lemmatizer {
-    maps: PartsOfSpeech<Mmap>,
+    maps: PartsOfSpeech<FileData>,
+    /// Irregular forms of each base form in the exceptions, the reverse of `maps`.
+    inflections: PartsOfSpeech<BTreeMap<String, Vec<String>>>,
 }
 
 impl Lemmatizer {
     pub fn new(dir: &Path) -> std::io::Result<Self> {
-        let maps = PartsOfSpeech::try_with(|pos| unsafe { Mmap::map(&Self::get_file(dir, pos)?) })?;
-        Ok(Self { maps })
+        let maps = PartsOfSpeech::try_with(|pos| file::open(dir, &Self::names(pos)))?;
+        Ok(Self::with_maps(maps))
+    }
+
+    pub fn from_bytes(files: &[(&str, &'static [u8])]) -> std::io::Result<Self> {
+        let maps = PartsOfSpeech::try_with(|pos| file::from_bytes(files, &Self::names(pos)))?;
+        Ok(Self::with_maps(maps))
     }
 
-    fn get_file(dir: &Path, pos: PartOfSpeech) -> std::io::Result<File> {
-        let p = dir.join(pos.as_suffix()).with_extension("exc");
-        File::open(p)
+    fn with_maps(maps: PartsOfSpeech<FileData>) -> Self {
+        let inflections = PartsOfSpeech::with(|pos| {
+            let mut inflections = BTreeMap::<String, Vec<String>>::new();
+            for line in maps
+                .get(pos)
+                .split(|b| *b == b'\n')
+                .filter_map(|line| std::str::from_utf8(line).ok())
+            {
+                let mut parts = line.split_whitespace();
+                let Some(inflected) = parts.next() else {
+                    continue;
+                };
+                for base in parts {
+                    inflections
+                        .entry(base.to_owned())
+                        .or_default()
+                        .push(inflected.to_owned());
+                }
+            }
+            inflections
+        });
+        Self { maps, inflections }
+    }
+
+    fn names(pos: PartOfSpeech) -> [String; 1] {
+        [format!("{}.exc", pos.as_suffix())]
     }
 
     fn exceptions_for(&self, index: &Index, word: &str, pos: PartOfSpeech) -> Vec<String> {
@@ -37,6 +66,15 @@ impl Lemmatizer {
         results
     }
 
+    /// Irregular forms listed in the exceptions with the given base form.
+    pub fn inflections(&self, base: &str, pos: PartOfSpeech) -> Vec<String> {
+        self.inflections
+            .get(pos)
+            .get(base)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn lemmatize(
         &self,
         word: &str,
@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use super::synset::SynSet;
+use super::WordNet;
+
+/// Common words that say nothing about which sense is meant.
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "an", "and", "any", "are", "as", "at", "be", "been", "being", "but", "by", "can",
+    "do", "does", "for", "from", "had", "has", "have", "he", "her", "his", "i", "if", "in", "into",
+    "is", "it", "its", "me", "my", "not", "of", "on", "one", "or", "our", "she", "so", "some",
+    "such", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "to",
+    "up", "us", "was", "we", "were", "what", "when", "which", "who", "will", "with", "would",
+    "you", "your",
+];
+
+/// Simplified Lesk word sense disambiguation.
+///
+/// Senses are scored by how many words of the context appear in their signature: the words of
+/// their definition, examples and lemmas, along with those of the synsets they are directly
+/// related to. Words are compared by their lemmas, so "ran" in the context matches "run".
+/// Signatures do not depend on the context, so they are kept in the synset cache when it is
+/// enabled.
+pub struct Lesk<'a> {
+    wn: &'a WordNet,
+    context: BTreeSet<String>,
+}
+
+impl<'a> Lesk<'a> {
+    /// Disambiguate against the words surrounding the one being looked up, which should not be
+    /// included itself.
+    pub fn new<S: AsRef<str>>(wn: &'a WordNet, context: &[S]) -> Self {
+        let mut lesk = Self {
+            wn,
+            context: BTreeSet::new(),
+        };
+        let mut words = BTreeSet::new();
+        for text in context {
+            lesk.extend(&mut words, text.as_ref());
+        }
+        lesk.context = words;
+        lesk
+    }
+
+    /// Number of context words found in the signature of the synset.
+    pub fn score(&self, synset: &SynSet) -> usize {
+        if self.context.is_empty() {
+            return 0;
+        }
+        let key = (
+            synset.part_of_speech,
+            synset
+                .lemmas
+                .first()
+                .map(|l| l.word.clone())
+                .unwrap_or_default(),
+            synset.definition.clone(),
+        );
+        let signature = self.wn.data.signature(key, || self.signature(synset));
+        self.context.intersection(&signature).count()
+    }
+
+    /// Synsets with their scores, best first, keeping the original order between equal scores.
+    ///
+    /// Without content words in the context or with a single synset there is nothing to choose
+    /// between, so every score is zero.
+    pub fn rank(&self, synsets: Vec<Arc<SynSet>>) -> Vec<(Arc<SynSet>, usize)> {
+        if self.context.is_empty() || synsets.len() < 2 {
+            return synsets.into_iter().map(|ss| (ss, 0)).collect();
+        }
+        let mut ranked = synsets
+            .into_iter()
+            .map(|ss| {
+                let score = self.score(&ss);
+                (ss, score)
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+        ranked
+    }
+
+    fn signature(&self, synset: &SynSet) -> BTreeSet<String> {
+        let mut signature = BTreeSet::new();
+        self.extend(&mut signature, &synset.definition);
+        for example in &synset.examples {
+            self.extend(&mut signature, example);
+        }
+        for lemma in &synset.lemmas {
+            self.extend(&mut signature, &lemma.word);
+        }
+        for r in &synset.relationships {
+            if let Some(related) = self.wn.resolve_ref(r.part_of_speech, r.synset_offset) {
+                self.extend(&mut signature, related.definition());
+                for lemma in related.lemmas() {
+                    self.extend(&mut signature, lemma);
+                }
+            }
+        }
+        signature
+    }
+
+    /// Add the content words of the text to the set, along with their lemmas.
+    fn extend(&self, words: &mut BTreeSet<String>, text: &str) {
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            let word = word.to_lowercase();
+            if STOP_WORDS.contains(&word.as_str()) || word.chars().all(|c| c.is_numeric()) {
+                continue;
+            }
+            self.wn
+                .lemmatize(&word)
+                .for_each(|_, lemmas| words.extend(lemmas));
+            words.insert(word);
+        }
+    }
+}
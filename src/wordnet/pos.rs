@@ -2,6 +2,8 @@ use std::fmt;
 
 /// PoS represents a part of speech
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum PartOfSpeech {
     Noun,
     Verb,
@@ -67,6 +69,7 @@ impl PartOfSpeech {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartsOfSpeech<T> {
     pub noun: T,
     pub verb: T,
@@ -137,6 +140,15 @@ impl<T> PartsOfSpeech<T> {
         }
     }
 
+    pub fn get_mut(&mut self, pos: PartOfSpeech) -> &mut T {
+        match pos {
+            PartOfSpeech::Noun => &mut self.noun,
+            PartOfSpeech::Verb => &mut self.verb,
+            PartOfSpeech::Adjective => &mut self.adjective,
+            PartOfSpeech::Adverb => &mut self.adverb,
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         [&self.noun, &self.verb, &self.adjective, &self.adverb].into_iter()
     }
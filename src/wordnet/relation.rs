@@ -1,6 +1,9 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum SemanticRelation {
     // @    Hypernym
     Hypernym,
@@ -51,6 +54,34 @@ pub enum SemanticRelation {
 }
 
 impl SemanticRelation {
+    pub fn variants() -> [SemanticRelation; 23] {
+        [
+            SemanticRelation::Hypernym,
+            SemanticRelation::InstanceHypernym,
+            SemanticRelation::Hyponym,
+            SemanticRelation::InstanceHyponym,
+            SemanticRelation::MemberHolonym,
+            SemanticRelation::SubstanceHolonym,
+            SemanticRelation::PartHolonym,
+            SemanticRelation::MemberMeronym,
+            SemanticRelation::SubstanceMeronym,
+            SemanticRelation::PartMeronym,
+            SemanticRelation::Attribute,
+            SemanticRelation::DomainOfSynsetTopic,
+            SemanticRelation::MemberOfThisDomainTopic,
+            SemanticRelation::DomainOfSynsetRegion,
+            SemanticRelation::MemberOfThisDomainRegion,
+            SemanticRelation::DomainOfSynsetUsage,
+            SemanticRelation::MemberOfThisDomainUsage,
+            SemanticRelation::Entailment,
+            SemanticRelation::Cause,
+            SemanticRelation::VerbGroup,
+            SemanticRelation::SimilarTo,
+            SemanticRelation::DerivedFromAdjective,
+            SemanticRelation::AlsoSee,
+        ]
+    }
+
     pub fn try_from_str(s: &str) -> Option<SemanticRelation> {
         match s {
             "@" => Some(SemanticRelation::Hypernym),
@@ -81,6 +112,19 @@ impl SemanticRelation {
     }
 }
 
+/// Parses the displayed name of a relation, with either spaces or underscores between words.
+impl FromStr for SemanticRelation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.replace('_', " ");
+        Self::variants()
+            .into_iter()
+            .find(|r| r.to_string() == name)
+            .ok_or_else(|| format!("unknown relation {s:?}"))
+    }
+}
+
 impl Display for SemanticRelation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -113,6 +157,8 @@ impl Display for SemanticRelation {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum LexicalRelation {
     // !    Antonym
     Antonym,
@@ -179,3 +225,33 @@ impl Display for LexicalRelation {
         f.write_str(s)
     }
 }
+
+/// Either kind of relation, for following semantic and lexical relations together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Relation {
+    Semantic(SemanticRelation),
+    Lexical(LexicalRelation),
+}
+
+impl From<SemanticRelation> for Relation {
+    fn from(relation: SemanticRelation) -> Self {
+        Relation::Semantic(relation)
+    }
+}
+
+impl From<LexicalRelation> for Relation {
+    fn from(relation: LexicalRelation) -> Self {
+        Relation::Lexical(relation)
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relation::Semantic(r) => r.fmt(f),
+            Relation::Lexical(r) => r.fmt(f),
+        }
+    }
+}
@@ -3,7 +3,8 @@ use super::{
     PartOfSpeech, WordNet,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SynSet {
     /// Lemmas within the synset.
     pub lemmas: Vec<Lemma>,
@@ -17,7 +18,8 @@ pub struct SynSet {
     pub relationships: Vec<SemanticRelationship>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SemanticRelationship {
     /// The kind of relationship to other synsets.
     pub relation: SemanticRelation,
@@ -27,7 +29,8 @@ pub struct SemanticRelationship {
     pub part_of_speech: PartOfSpeech,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LexicalRelationship {
     /// The kind of relationship to other synsets.
     pub relation: LexicalRelation,
@@ -50,9 +53,22 @@ impl SynSet {
     pub fn synonyms(&self) -> Vec<String> {
         self.lemmas.iter().map(|l| l.word.to_owned()).collect()
     }
+
+    /// Find the lemma for a word ignoring case, to recover the casing used in the data files.
+    pub fn lemma(&self, word: &str) -> Option<&Lemma> {
+        self.lemmas
+            .iter()
+            .find(|l| l.word.to_lowercase() == word.to_lowercase())
+    }
+
+    /// Whether the synset has a lemma for the word with exactly the same casing.
+    pub fn has_exact_lemma(&self, word: &str) -> bool {
+        self.lemmas.iter().any(|l| l.word == word)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lemma {
     pub word: String,
     pub part_of_speech: PartOfSpeech,
@@ -79,7 +95,7 @@ impl Lemma {
                         .expect("Failed to resolve word from lemma relationship"),
                 )
             })
-            .map(|(target, mut ss)| ss.lemmas.remove(target).word)
+            .map(|(target, ss)| ss.lemmas[target].word.clone())
             .collect::<Vec<_>>();
         antonyms.sort_unstable();
         antonyms.dedup();
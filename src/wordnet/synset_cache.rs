@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use super::pos::PartOfSpeech;
+use super::synset::SynSet;
+
+/// Part of speech, first lemma and definition, which between them identify a sense even when it
+/// has no location in the data files, such as one from a user dictionary.
+pub type SenseKey = (PartOfSpeech, String, String);
+
+/// Bounded least-recently-used cache of parsed synsets, keyed by their location in the data files,
+/// along with the signatures used to disambiguate them.
+#[derive(Debug)]
+pub struct SynSetCache {
+    capacity: usize,
+    synsets: Mutex<Lru<(PartOfSpeech, u64), Arc<SynSet>>>,
+    signatures: Mutex<Lru<SenseKey, Arc<BTreeSet<String>>>>,
+}
+
+#[derive(Debug)]
+struct Lru<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    /// Last use of each entry, oldest first.
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Usage of the synset cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookups that had to parse the data files.
+    pub misses: u64,
+    /// Synsets currently in the cache.
+    pub len: usize,
+    /// Maximum number of synsets kept in the cache.
+    pub capacity: usize,
+}
+
+impl SynSetCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            synsets: Mutex::new(Lru::default()),
+            signatures: Mutex::new(Lru::default()),
+        }
+    }
+
+    pub fn get(&self, pos: PartOfSpeech, offset: u64) -> Option<Arc<SynSet>> {
+        self.synsets.lock().unwrap().get(&(pos, offset))
+    }
+
+    pub fn insert(&self, pos: PartOfSpeech, offset: u64, synset: Arc<SynSet>) {
+        let mut synsets = self.synsets.lock().unwrap();
+        synsets.insert((pos, offset), synset, self.capacity);
+    }
+
+    pub fn signature(&self, key: &SenseKey) -> Option<Arc<BTreeSet<String>>> {
+        self.signatures.lock().unwrap().get(key)
+    }
+
+    pub fn insert_signature(&self, key: SenseKey, signature: Arc<BTreeSet<String>>) {
+        let mut signatures = self.signatures.lock().unwrap();
+        signatures.insert(key, signature, self.capacity);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let synsets = self.synsets.lock().unwrap();
+        CacheStats {
+            hits: synsets.hits,
+            misses: synsets.misses,
+            len: synsets.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<K, V> Default for Lru<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some((value, last_used)) => {
+                self.recency.remove(last_used);
+                *last_used = self.tick;
+                self.recency.insert(self.tick, key.clone());
+                self.hits += 1;
+                Some(value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(self.tick, key);
+        while self.entries.len() > capacity {
+            match self.recency.pop_first() {
+                Some((_, key)) => {
+                    self.entries.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::str::SplitAsciiWhitespace;
+
+use super::graph::SynSetId;
+use super::pos::PartOfSpeech;
+use super::relation::{LexicalRelation, SemanticRelation};
+use super::synset::{LexicalRelationship, SemanticRelationship, SynSet};
+
+/// A synset borrowed straight out of a memory mapped data file.
+///
+/// Nothing is parsed up front, each accessor walks the line when called, so scanning many synsets
+/// does not allocate. Use [`SynSetRef::to_synset`] to get an owned [`SynSet`].
+#[derive(Debug, Clone, Copy)]
+pub struct SynSetRef<'a> {
+    line: &'a str,
+    offset: u64,
+    part_of_speech: PartOfSpeech,
+}
+
+impl<'a> SynSetRef<'a> {
+    pub(super) fn from_line(line: &'a str) -> Option<Self> {
+        let mut fields = line.split_ascii_whitespace();
+        let offset = fields.next()?.parse().ok()?;
+        let _lex_filenum = fields.next()?;
+        let part_of_speech = PartOfSpeech::try_from_str(fields.next()?)?;
+        Some(Self {
+            line,
+            offset,
+            part_of_speech,
+        })
+    }
+
+    /// Offset in the data file for the part of speech.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// What type of word it is.
+    pub fn part_of_speech(&self) -> PartOfSpeech {
+        self.part_of_speech
+    }
+
+    /// Identifier of the synset, for queries such as [`super::WordNet::neighbourhood`].
+    pub fn id(&self) -> SynSetId {
+        SynSetId {
+            part_of_speech: self.part_of_speech,
+            offset: self.offset,
+        }
+    }
+
+    /// Words of the lemmas within the synset.
+    pub fn lemmas(&self) -> impl Iterator<Item = &'a str> {
+        self.fields().skip(4).step_by(2).take(self.lemma_count())
+    }
+
+    /// How it relates to other synsets.
+    pub fn relationships(&self) -> impl Iterator<Item = SemanticRelationship> + 'a {
+        self.pointers()
+            .filter(|(_, _, _, source_target)| *source_target == "0000")
+            .filter_map(|(symbol, synset_offset, part_of_speech, _)| {
+                Some(SemanticRelationship {
+                    relation: SemanticRelation::try_from_str(symbol)?,
+                    synset_offset,
+                    part_of_speech,
+                })
+            })
+    }
+
+    pub fn with_relationship(
+        &self,
+        relation: SemanticRelation,
+    ) -> impl Iterator<Item = SemanticRelationship> + 'a {
+        self.relationships().filter(move |r| r.relation == relation)
+    }
+
+    /// Lexical relationships of the lemmas, along with the index of the lemma they belong to.
+    pub fn lexical_relationships(&self) -> impl Iterator<Item = (usize, LexicalRelationship)> + 'a {
+        self.pointers()
+            .filter(|(_, _, _, source_target)| *source_target != "0000")
+            .filter_map(|(symbol, synset_offset, part_of_speech, source_target)| {
+                let (source, target) = source_target.split_at_checked(2)?;
+                let source = usize::from_str_radix(source, 16).ok()?;
+                let target = usize::from_str_radix(target, 16).ok()?;
+                let relationship = LexicalRelationship {
+                    relation: LexicalRelation::try_from_str(symbol)?,
+                    synset_offset,
+                    part_of_speech,
+                    target: target.checked_sub(1)?,
+                };
+                Some((source.checked_sub(1)?, relationship))
+            })
+    }
+
+    /// Glossary entry, the definition followed by any examples.
+    pub fn gloss(&self) -> &'a str {
+        self.line
+            .split_once(" |")
+            .map_or("", |(_, gloss)| gloss.trim())
+    }
+
+    pub fn definition(&self) -> &'a str {
+        self.gloss().split("; \"").next().unwrap_or_default()
+    }
+
+    /// Example uses.
+    pub fn examples(&self) -> impl Iterator<Item = &'a str> {
+        self.gloss()
+            .split("; \"")
+            .skip(1)
+            .filter_map(|s| s.strip_suffix('"'))
+    }
+
+    /// Parse the full synset into owned values.
+    pub fn to_synset(&self) -> Option<SynSet> {
+        SynSet::from_parts(self.fields())
+    }
+
+    fn fields(&self) -> SplitAsciiWhitespace<'a> {
+        self.line.split_ascii_whitespace()
+    }
+
+    fn lemma_count(&self) -> usize {
+        self.fields()
+            .nth(3)
+            .and_then(|w_cnt| usize::from_str_radix(w_cnt, 16).ok())
+            .unwrap_or_default()
+    }
+
+    /// Raw pointers as (pointer symbol, synset offset, part of speech, source/target).
+    fn pointers(&self) -> impl Iterator<Item = (&'a str, u64, PartOfSpeech, &'a str)> {
+        let mut fields = self.fields().skip(4 + 2 * self.lemma_count());
+        let p_cnt = fields
+            .next()
+            .and_then(|p_cnt| p_cnt.parse::<usize>().ok())
+            .unwrap_or_default();
+        (0..p_cnt).map_while(move |_| {
+            let pointer_symbol = fields.next()?;
+            let synset_offset = fields.next()?.parse::<u64>().ok()?;
+            let part_of_speech = PartOfSpeech::try_from_str(fields.next()?)?;
+            let source_target = fields.next()?;
+            Some((pointer_symbol, synset_offset, part_of_speech, source_target))
+        })
+    }
+}
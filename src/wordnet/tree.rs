@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::graph::SynSetId;
+use super::relation::SemanticRelation;
+use super::synset::SynSet;
+use super::WordNet;
+
+/// Synsets reached by repeatedly following relations from a synset, such as its hyponyms.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tree {
+    pub id: SynSetId,
+    pub synset: Arc<SynSet>,
+    /// Number of distinct synsets below this one, including those beyond the depth of the tree.
+    pub descendants: usize,
+    /// Synsets directly related to this one, empty once the depth of the tree is reached.
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    /// Follow `relations` from `id` for up to `depth` levels. A synset is never repeated within its
+    /// own branch, so cyclic relations terminate.
+    pub(super) fn build(
+        wn: &WordNet,
+        id: SynSetId,
+        relations: &[SemanticRelation],
+        depth: usize,
+    ) -> Option<Self> {
+        let mut reachable = Reachable::new(wn, id, relations);
+        Self::build_branch(wn, id, depth, &mut reachable, &mut BTreeSet::new())
+    }
+
+    fn build_branch(
+        wn: &WordNet,
+        id: SynSetId,
+        depth: usize,
+        reachable: &mut Reachable,
+        ancestors: &mut BTreeSet<SynSetId>,
+    ) -> Option<Self> {
+        let synset = wn.resolve(id.part_of_speech, id.offset)?;
+        let descendants = reachable.from(id).iter().filter(|&&d| d != id).count();
+        let mut children = Vec::new();
+        if depth > 0 {
+            ancestors.insert(id);
+            for target in reachable.targets(id).to_vec() {
+                if ancestors.contains(&target) {
+                    continue;
+                }
+                if let Some(child) = Self::build_branch(wn, target, depth - 1, reachable, ancestors)
+                {
+                    children.push(child);
+                }
+            }
+            ancestors.remove(&id);
+        }
+        Some(Self {
+            id,
+            synset,
+            descendants,
+            children,
+        })
+    }
+
+    /// Every path from this synset to a leaf of the tree, excluding this synset itself.
+    pub fn chains(&self) -> Vec<Vec<&SynSet>> {
+        self.children
+            .iter()
+            .flat_map(|child| {
+                let chains = child.chains();
+                if chains.is_empty() {
+                    vec![vec![&*child.synset]]
+                } else {
+                    chains
+                        .into_iter()
+                        .map(|chain| std::iter::once(&*child.synset).chain(chain).collect())
+                        .collect()
+                }
+            })
+            .collect()
+    }
+
+    /// Greatest number of levels below this synset in the tree.
+    pub fn height(&self) -> usize {
+        self.children
+            .iter()
+            .map(|c| c.height() + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn targets(wn: &WordNet, id: SynSetId, relations: &[SemanticRelation]) -> Vec<SynSetId> {
+        wn.resolve_ref(id.part_of_speech, id.offset)
+            .map(|ss| {
+                ss.relationships()
+                    .filter(|r| relations.contains(&r.relation))
+                    .map(|r| SynSetId {
+                        part_of_speech: r.part_of_speech,
+                        offset: r.synset_offset,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The synsets reachable from the root of a tree, found with a single traversal so that the
+/// descendants of every synset in the tree are counted without walking its relations again.
+struct Reachable {
+    targets: BTreeMap<SynSetId, Vec<SynSetId>>,
+    /// Synsets reachable from each synset, filled in bottom up as they are asked for.
+    from: BTreeMap<SynSetId, Rc<BTreeSet<SynSetId>>>,
+    /// Synsets whose reachable synsets are being collected, to detect cycles.
+    visiting: BTreeSet<SynSetId>,
+}
+
+impl Reachable {
+    fn new(wn: &WordNet, root: SynSetId, relations: &[SemanticRelation]) -> Self {
+        let mut targets = BTreeMap::new();
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            if targets.contains_key(&id) {
+                continue;
+            }
+            let ids = Tree::targets(wn, id, relations);
+            stack.extend(ids.iter().copied());
+            targets.insert(id, ids);
+        }
+        Self {
+            targets,
+            from: BTreeMap::new(),
+            visiting: BTreeSet::new(),
+        }
+    }
+
+    fn targets(&self, id: SynSetId) -> &[SynSetId] {
+        self.targets.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every synset reachable from `id`, which includes `id` itself only through a cycle.
+    fn from(&mut self, id: SynSetId) -> Rc<BTreeSet<SynSetId>> {
+        if let Some(reachable) = self.from.get(&id) {
+            return reachable.clone();
+        }
+        let targets = self.targets(id).to_vec();
+        let reachable = if targets.iter().any(|t| self.visiting.contains(t)) {
+            // part of a cycle whose other synsets are not done yet, so walk it instead
+            self.walk(id)
+        } else {
+            self.visiting.insert(id);
+            let mut reachable = BTreeSet::new();
+            for target in targets {
+                reachable.insert(target);
+                reachable.extend(self.from(target).iter().copied());
+            }
+            self.visiting.remove(&id);
+            reachable
+        };
+        let reachable = Rc::new(reachable);
+        self.from.insert(id, reachable.clone());
+        reachable
+    }
+
+    fn walk(&self, id: SynSetId) -> BTreeSet<SynSetId> {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            for &target in self.targets(id) {
+                if seen.insert(target) {
+                    stack.push(target);
+                }
+            }
+        }
+        seen
+    }
+}
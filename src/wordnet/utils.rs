@@ -1,6 +1,4 @@
-use memmap::Mmap;
-
-pub fn binary_search_file(map: &Mmap, word: &str) -> Option<String> {
+pub fn binary_search_file(map: &[u8], word: &str) -> Option<String> {
     let mut start = 0_usize;
     let mut end = map.len();
 